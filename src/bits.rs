@@ -0,0 +1,528 @@
+//! A double-ended queue of bits.
+
+use super::*;
+
+/// Reads `n <= 64` bits starting `offset` bits into `bytes`, most
+/// significant bit first.
+///
+/// `bytes` must contain at least `offset + n` bits.
+#[inline]
+fn read_bits(bytes: &[u8], offset: usize, n: usize) -> u64 {
+    debug_assert!(n <= 64);
+    debug_assert!(offset + n <= bytes.len() * 8);
+    if n == 0 {
+        return 0;
+    }
+    let first = offset / 8;
+    let bit = offset % 8;
+    // At most 9 bytes are needed for 64 bits at an arbitrary bit offset:
+    let last = (offset + n).div_ceil(8);
+    let mut acc: u128 = 0;
+    for &b in &bytes[first..last] {
+        acc = (acc << 8) | u128::from(b);
+    }
+    let shift = (last - first) * 8 - bit - n;
+    ((acc >> shift) & ((1_u128 << n) - 1)) as u64
+}
+
+/// A double-ended queue of bits that can be read as a contiguous bit slice.
+///
+/// Bits are stored most significant bit first in a `SliceDeque<u8>`: the
+/// first bit of the deque is the most significant bit of the first byte
+/// (possibly after some bits that were already popped). Since the bytes are
+/// always contiguous in memory, reading a value that straddles the end of
+/// the ring buffer does not need any special casing, which is what bitstream
+/// readers such as Huffman or arithmetic decoders need.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::BitSliceDeque;
+/// let mut bits = BitSliceDeque::new();
+/// bits.push_back_bits(0b101, 3);
+/// bits.push_back_bytes(&[0xff, 0x00]);
+/// bits.push_back_bit(true);
+/// assert_eq!(bits.len(), 20);
+/// assert_eq!(bits.pop_front_bits(4), 0b1011);
+/// assert_eq!(bits.pop_front_bits(16), 0b1111_1110_0000_0001);
+/// assert!(bits.is_empty());
+/// ```
+#[derive(Clone, Default)]
+pub struct BitSliceDeque {
+    /// Bytes holding the bits.
+    bytes: SliceDeque<u8>,
+    /// Number of bits of the first byte that have been popped.
+    head: usize,
+    /// Number of bits in the deque.
+    len: usize,
+}
+
+impl BitSliceDeque {
+    /// Creates a new empty deque of bits.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            bytes: SliceDeque::new(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Creates a new empty deque with capacity for at least `n` bits.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            bytes: SliceDeque::with_capacity(n.div_ceil(8)),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Number of bits in the deque.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the deque empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of bits that the deque can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity() * 8 - self.head
+    }
+
+    /// Returns a view of the bits in the deque.
+    #[inline]
+    pub fn as_bit_slice(&self) -> BitSlice<'_> {
+        BitSlice {
+            bytes: &self.bytes,
+            offset: self.head,
+            len: self.len,
+        }
+    }
+
+    /// Appends the bit `bit`.
+    #[inline]
+    pub fn push_back_bit(&mut self, bit: bool) {
+        self.push_back_bits(u64::from(bit), 1);
+    }
+
+    /// Appends the `n` least significant bits of `value`, most significant
+    /// bit first.
+    ///
+    /// # Panics
+    ///
+    /// If `n > 64`.
+    #[inline]
+    pub fn push_back_bits(&mut self, value: u64, n: usize) {
+        assert!(n <= 64, "cannot push {} bits at once", n);
+        let mut remaining = n;
+        let mut end = self.head + self.len;
+        self.bytes.reserve((end % 8 + n).div_ceil(8));
+        while remaining > 0 {
+            let bit = end % 8;
+            if bit == 0 {
+                self.bytes.push_back(0);
+            }
+            let k = cmp::min(8 - bit, remaining);
+            let chunk = ((value >> (remaining - k)) & ((1 << k) - 1)) as u8;
+            if let Some(last) = self.bytes.back_mut() {
+                *last |= chunk << (8 - bit - k);
+            }
+            remaining -= k;
+            end += k;
+        }
+        self.len += n;
+    }
+
+    /// Appends the bits of `bytes`.
+    #[inline]
+    pub fn push_back_bytes(&mut self, bytes: &[u8]) {
+        if (self.head + self.len).is_multiple_of(8) {
+            self.bytes.extend_from_slice(bytes);
+            self.len += bytes.len() * 8;
+        } else {
+            for &b in bytes {
+                self.push_back_bits(u64::from(b), 8);
+            }
+        }
+    }
+
+    /// Returns the first `n` bits without removing them, with the first bit
+    /// as the most significant one.
+    ///
+    /// # Panics
+    ///
+    /// If `n > 64` or the deque has fewer than `n` bits.
+    #[inline]
+    pub fn peek_front_bits(&self, n: usize) -> u64 {
+        assert!(n <= 64, "cannot read {} bits at once", n);
+        assert!(
+            n <= self.len,
+            "cannot read {} bits, the deque has {} bits",
+            n,
+            self.len
+        );
+        read_bits(&self.bytes, self.head, n)
+    }
+
+    /// Removes the first `n` bits and returns them, with the first bit as
+    /// the most significant one.
+    ///
+    /// # Panics
+    ///
+    /// If `n > 64` or the deque has fewer than `n` bits.
+    #[inline]
+    pub fn pop_front_bits(&mut self, n: usize) -> u64 {
+        let value = self.peek_front_bits(n);
+        self.skip_front_bits(n);
+        value
+    }
+
+    /// Removes the first bit and returns it, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn pop_front_bit(&mut self) -> Option<bool> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.pop_front_bits(1) != 0)
+    }
+
+    /// Removes the first `n` bits.
+    ///
+    /// # Panics
+    ///
+    /// If the deque has fewer than `n` bits.
+    #[inline]
+    pub fn skip_front_bits(&mut self, n: usize) {
+        assert!(
+            n <= self.len,
+            "cannot skip {} bits, the deque has {} bits",
+            n,
+            self.len
+        );
+        self.head += n;
+        self.len -= n;
+        let bytes = self.bytes.len() - self.head / 8;
+        self.bytes.truncate_front(bytes);
+        self.head %= 8;
+        if self.len == 0 {
+            self.clear();
+        }
+    }
+
+    /// Removes the bits before the next byte boundary, and returns how many
+    /// bits were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BitSliceDeque;
+    /// let mut bits = BitSliceDeque::new();
+    /// bits.push_back_bytes(&[0b1010_0000, 0x42]);
+    /// bits.pop_front_bits(3);
+    /// assert_eq!(bits.align_front(), 5);
+    /// assert_eq!(bits.pop_front_bits(8), 0x42);
+    /// ```
+    #[inline]
+    pub fn align_front(&mut self) -> usize {
+        let n = cmp::min((8 - self.head % 8) % 8, self.len);
+        self.skip_front_bits(n);
+        n
+    }
+
+    /// Removes all bits from the deque.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+impl fmt::Debug for BitSliceDeque {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_bit_slice(), f)
+    }
+}
+
+impl PartialEq for BitSliceDeque {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bit_slice() == other.as_bit_slice()
+    }
+}
+
+impl Eq for BitSliceDeque {}
+
+impl Extend<bool> for BitSliceDeque {
+    #[inline]
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for bit in iter {
+            self.push_back_bit(bit);
+        }
+    }
+}
+
+impl iter::FromIterator<bool> for BitSliceDeque {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut deq = Self::new();
+        deq.extend(iter);
+        deq
+    }
+}
+
+/// A contiguous view of the bits in a [`BitSliceDeque`].
+///
+/// [`BitSliceDeque`]: struct.BitSliceDeque.html
+#[derive(Copy, Clone)]
+pub struct BitSlice<'a> {
+    /// Bytes holding the bits.
+    bytes: &'a [u8],
+    /// Offset of the first bit into `bytes`.
+    offset: usize,
+    /// Number of bits.
+    len: usize,
+}
+
+impl<'a> BitSlice<'a> {
+    /// Number of bits in the slice.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the slice empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit at `index`, or `None` if it is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(read_bits(self.bytes, self.offset + index, 1) != 0)
+    }
+
+    /// Reads `n` bits starting at bit `index`, with the first bit as the
+    /// most significant one.
+    ///
+    /// # Panics
+    ///
+    /// If `n > 64` or the range of bits is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BitSliceDeque;
+    /// let mut bits = BitSliceDeque::new();
+    /// bits.push_back_bytes(&[0x12, 0x34]);
+    /// assert_eq!(bits.as_bit_slice().read(4, 8), 0x23);
+    /// ```
+    #[inline]
+    pub fn read(&self, index: usize, n: usize) -> u64 {
+        assert!(n <= 64, "cannot read {} bits at once", n);
+        assert!(
+            index <= self.len && n <= self.len - index,
+            "bits {}..{} out of bounds of {} bits",
+            index,
+            index.saturating_add(n),
+            self.len
+        );
+        read_bits(self.bytes, self.offset + index, n)
+    }
+
+    /// Returns a sub-slice with the bits in `range`.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds.
+    #[inline]
+    pub fn slice(&self, range: ops::Range<usize>) -> BitSlice<'a> {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "bits {}..{} out of bounds of {} bits",
+            range.start,
+            range.end,
+            self.len
+        );
+        BitSlice {
+            bytes: self.bytes,
+            offset: self.offset + range.start,
+            len: range.end - range.start,
+        }
+    }
+
+    /// Returns the bytes holding the bits, and the offset of the first bit
+    /// into the first byte.
+    ///
+    /// The bits before the offset and after the end of the slice have
+    /// unspecified values.
+    #[inline]
+    pub fn as_raw_bytes(&self) -> (&'a [u8], usize) {
+        let first = self.offset / 8;
+        let last = (self.offset + self.len).div_ceil(8);
+        (&self.bytes[first..last], self.offset % 8)
+    }
+
+    /// Iterates over the bits of the slice.
+    #[inline]
+    pub fn iter(&self) -> BitIter<'a> {
+        BitIter {
+            slice: *self,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> PartialEq for BitSlice<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+        let mut i = 0;
+        while i < self.len {
+            let n = cmp::min(64, self.len - i);
+            if self.read(i, n) != other.read(i, n) {
+                return false;
+            }
+            i += n;
+        }
+        true
+    }
+}
+
+impl<'a> Eq for BitSlice<'a> {}
+
+impl<'a> fmt::Debug for BitSlice<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[")?;
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<'a> IntoIterator for BitSlice<'a> {
+    type Item = bool;
+    type IntoIter = BitIter<'a>;
+    #[inline]
+    fn into_iter(self) -> BitIter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the bits of a [`BitSlice`].
+///
+/// [`BitSlice`]: struct.BitSlice.html
+#[derive(Clone, Debug)]
+pub struct BitIter<'a> {
+    /// The bits iterated over.
+    slice: BitSlice<'a>,
+    /// Index of the next bit.
+    index: usize,
+}
+
+impl<'a> Iterator for BitIter<'a> {
+    type Item = bool;
+    #[inline]
+    fn next(&mut self) -> Option<bool> {
+        let bit = self.slice.get(self.index)?;
+        self.index += 1;
+        Some(bit)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.slice.len - self.index;
+        (n, Some(n))
+    }
+}
+
+impl<'a> ExactSizeIterator for BitIter<'a> {}
+
+impl<'a> iter::FusedIterator for BitIter<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_pop_bits() {
+        let mut bits = BitSliceDeque::new();
+        assert_eq!(bits.pop_front_bit(), None);
+        bits.push_back_bits(u64::MAX, 64);
+        bits.push_back_bits(0, 0);
+        bits.push_back_bits(0x1234_5678_9abc_def0, 61);
+        assert_eq!(bits.len(), 125);
+        assert_eq!(bits.pop_front_bits(3), 0b111);
+        assert_eq!(bits.pop_front_bits(64), u64::MAX << 3 | 0b100);
+        assert_eq!(bits.peek_front_bits(58), 0x0234_5678_9abc_def0);
+        assert_eq!(bits.pop_front_bits(58), 0x0234_5678_9abc_def0);
+        assert!(bits.is_empty());
+        assert_eq!(bits.pop_front_bits(0), 0);
+    }
+
+    #[test]
+    fn across_the_seam() {
+        let mut bits = BitSliceDeque::with_capacity(64);
+        let byte_cap = bits.bytes.capacity();
+        // Move the head of the byte deque close to the end of the buffer:
+        for _ in 0..byte_cap - 3 {
+            bits.push_back_bits(0xa5, 8);
+            assert_eq!(bits.pop_front_bits(8), 0xa5);
+        }
+        let mut expected = Vec::new();
+        let mut x = 0x1357_u64;
+        for i in 0..200 {
+            x = x.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let n = i % 17;
+            bits.push_back_bits(x, n);
+            expected.push((x & ((1 << n) - 1), n));
+            if bits.len() > 40 {
+                let (v, n) = expected.remove(0);
+                assert_eq!(bits.pop_front_bits(n), v);
+            }
+        }
+        let slice = bits.as_bit_slice();
+        let mut idx = 0;
+        for &(v, n) in &expected {
+            assert_eq!(slice.read(idx, n), v);
+            idx += n;
+        }
+        assert_eq!(idx, slice.len());
+        assert_eq!(bits.bytes.capacity(), byte_cap);
+    }
+
+    #[test]
+    fn bit_slice() {
+        let bits: BitSliceDeque =
+            [true, false, true, true, false].iter().cloned().collect();
+        let s = bits.as_bit_slice();
+        assert_eq!(
+            s.iter().collect::<Vec<_>>(),
+            [true, false, true, true, false]
+        );
+        assert_eq!(s.get(2), Some(true));
+        assert_eq!(s.get(5), None);
+        assert_eq!(format!("{:?}", s.slice(1..4)), "[011]");
+        assert_eq!(s.slice(2..3), s.slice(0..1));
+        assert_ne!(s.slice(1..4), s.slice(2..5));
+        assert_eq!(s.slice(3..5).as_raw_bytes(), (&[0b1011_0000][..], 3));
+        assert_eq!(bits, bits.clone());
+    }
+}
@@ -0,0 +1,402 @@
+//! A fixed-capacity double-ended queue that `Deref`s into a slice.
+
+use super::*;
+
+/// A fixed-capacity double-ended queue that derefs into a slice.
+///
+/// A `BoundedSliceDeque` allocates its storage once on construction and
+/// never reallocates afterwards: pushing into a full deque either evicts the
+/// element at the opposite end ([`push_back_overwrite`]) or hands the new
+/// element back ([`try_push_back`]). This makes it a ring buffer suitable for
+/// delay lines, sliding histories, etc.
+///
+/// [`push_back_overwrite`]: #method.push_back_overwrite
+/// [`try_push_back`]: #method.try_push_back
+pub struct BoundedSliceDeque<T> {
+    /// Elements in the queue.
+    deq: SliceDeque<T>,
+    /// Maximum number of elements in the queue.
+    cap: usize,
+}
+
+impl<T> BoundedSliceDeque<T> {
+    /// Creates a new empty deque that can hold up to `n` elements.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BoundedSliceDeque;
+    /// let deq = BoundedSliceDeque::with_capacity(10);
+    /// assert_eq!(deq.capacity(), 10);
+    /// # let o: BoundedSliceDeque<u32> = deq;
+    /// ```
+    #[inline]
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            deq: SliceDeque::with_capacity(n),
+            cap: n,
+        }
+    }
+
+    /// Maximum number of elements that the deque can hold.
+    ///
+    /// Unlike `SliceDeque::capacity` this is exactly the capacity that the
+    /// deque was created with, and not rounded up to the allocation
+    /// granularity.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Number of elements in the deque.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.deq.len()
+    }
+
+    /// Is the deque full ?
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BoundedSliceDeque;
+    /// let mut deq = BoundedSliceDeque::with_capacity(1);
+    /// assert!(!deq.is_full());
+    /// deq.push_back_overwrite(3);
+    /// assert!(deq.is_full());
+    /// ```
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap
+    }
+
+    /// Extracts a slice containing the entire deque.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.deq.as_slice()
+    }
+
+    /// Extracts a mutable slice containing the entire deque.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.deq.as_mut_slice()
+    }
+
+    /// Attempts to append `value` to the deque.
+    ///
+    /// If the deque is full, `value` is returned back.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BoundedSliceDeque;
+    /// let mut deq = BoundedSliceDeque::with_capacity(2);
+    /// assert_eq!(deq.try_push_back(1), Ok(()));
+    /// assert_eq!(deq.try_push_back(2), Ok(()));
+    /// assert_eq!(deq.try_push_back(3), Err(3));
+    /// assert_eq!(deq, [1, 2]);
+    /// ```
+    #[inline]
+    pub fn try_push_back(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        debug_assert!(!self.deq.is_full());
+        self.deq.push_back(value);
+        Ok(())
+    }
+
+    /// Attempts to prepend `value` to the deque.
+    ///
+    /// If the deque is full, `value` is returned back.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BoundedSliceDeque;
+    /// let mut deq = BoundedSliceDeque::with_capacity(2);
+    /// assert_eq!(deq.try_push_front(1), Ok(()));
+    /// assert_eq!(deq.try_push_front(2), Ok(()));
+    /// assert_eq!(deq.try_push_front(3), Err(3));
+    /// assert_eq!(deq, [2, 1]);
+    /// ```
+    #[inline]
+    pub fn try_push_front(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        debug_assert!(!self.deq.is_full());
+        self.deq.push_front(value);
+        Ok(())
+    }
+
+    /// Appends `value` to the deque, removing the first element if the deque
+    /// is full.
+    ///
+    /// Returns the removed element, if any. If the capacity of the deque is
+    /// zero, `value` itself is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BoundedSliceDeque;
+    /// let mut deq = BoundedSliceDeque::with_capacity(2);
+    /// assert_eq!(deq.push_back_overwrite(1), None);
+    /// assert_eq!(deq.push_back_overwrite(2), None);
+    /// assert_eq!(deq.push_back_overwrite(3), Some(1));
+    /// assert_eq!(deq, [2, 3]);
+    /// ```
+    #[inline]
+    pub fn push_back_overwrite(&mut self, value: T) -> Option<T> {
+        if self.cap == 0 {
+            return Some(value);
+        }
+        let evicted = if self.is_full() {
+            self.deq.pop_front()
+        } else {
+            None
+        };
+        debug_assert!(!self.deq.is_full());
+        self.deq.push_back(value);
+        evicted
+    }
+
+    /// Prepends `value` to the deque, removing the last element if the deque
+    /// is full.
+    ///
+    /// Returns the removed element, if any. If the capacity of the deque is
+    /// zero, `value` itself is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::BoundedSliceDeque;
+    /// let mut deq = BoundedSliceDeque::with_capacity(2);
+    /// assert_eq!(deq.push_front_overwrite(1), None);
+    /// assert_eq!(deq.push_front_overwrite(2), None);
+    /// assert_eq!(deq.push_front_overwrite(3), Some(1));
+    /// assert_eq!(deq, [3, 2]);
+    /// ```
+    #[inline]
+    pub fn push_front_overwrite(&mut self, value: T) -> Option<T> {
+        if self.cap == 0 {
+            return Some(value);
+        }
+        let evicted = if self.is_full() {
+            self.deq.pop_back()
+        } else {
+            None
+        };
+        debug_assert!(!self.deq.is_full());
+        self.deq.push_front(value);
+        evicted
+    }
+
+    /// Removes the first element and returns it, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.deq.pop_front()
+    }
+
+    /// Removes the last element and returns it, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.deq.pop_back()
+    }
+
+    /// Shortens the deque by removing excess elements from the front.
+    ///
+    /// If `len` is greater than the deque's current length, this has no
+    /// effect.
+    #[inline]
+    pub fn truncate_front(&mut self, len: usize) {
+        self.deq.truncate_front(len);
+    }
+
+    /// Shortens the deque by removing excess elements from the back.
+    ///
+    /// If `len` is greater than the deque's current length, this has no
+    /// effect.
+    #[inline]
+    pub fn truncate_back(&mut self, len: usize) {
+        self.deq.truncate_back(len);
+    }
+
+    /// Removes all values from the deque.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.deq.clear();
+    }
+
+    /// Converts the bounded deque into an unbounded `SliceDeque`.
+    ///
+    /// This does not reallocate.
+    #[inline]
+    pub fn into_inner(self) -> SliceDeque<T> {
+        self.deq
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BoundedSliceDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", self.as_slice())
+    }
+}
+
+impl<T> ops::Deref for BoundedSliceDeque<T> {
+    type Target = [T];
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T> ops::DerefMut for BoundedSliceDeque<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone> Clone for BoundedSliceDeque<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let mut new = Self::with_capacity(self.cap);
+        new.deq.extend_from_slice(self.as_slice());
+        new
+    }
+}
+
+impl<T: hash::Hash> hash::Hash for BoundedSliceDeque<T> {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        hash::Hash::hash(&**self, state)
+    }
+}
+
+impl<T> convert::AsRef<[T]> for BoundedSliceDeque<T> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> convert::AsMut<[T]> for BoundedSliceDeque<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<A, B> PartialEq<BoundedSliceDeque<B>> for BoundedSliceDeque<A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &BoundedSliceDeque<B>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq> Eq for BoundedSliceDeque<T> {}
+
+impl<'b, A, B> PartialEq<&'b [B]> for BoundedSliceDeque<A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &&'b [B]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<A, B, const N: usize> PartialEq<[B; N]> for BoundedSliceDeque<A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &[B; N]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn capacity_is_exact() {
+        let page_size = crate::mirrored::allocation_granularity();
+        let mut deq = BoundedSliceDeque::with_capacity(3);
+        assert_eq!(deq.capacity(), 3);
+        for i in 0..page_size * 3 {
+            deq.push_back_overwrite(i);
+            assert!(deq.len() <= 3);
+        }
+        assert_eq!(
+            deq,
+            [page_size * 3 - 3, page_size * 3 - 2, page_size * 3 - 1]
+        );
+    }
+
+    #[test]
+    fn never_reallocates() {
+        let page_size = crate::mirrored::allocation_granularity();
+        let mut deq = BoundedSliceDeque::<u8>::with_capacity(page_size);
+        let buf =
+            |d: &BoundedSliceDeque<u8>| unsafe { d.deq.buf.ptr() as usize };
+        let ptr = buf(&deq);
+        for i in 0..page_size * 4 {
+            deq.push_back_overwrite(i as u8);
+            if i % 3 == 0 {
+                deq.push_front_overwrite(i as u8);
+            }
+            assert_eq!(buf(&deq), ptr);
+        }
+        assert!(deq.is_full());
+        assert_eq!(deq.try_push_back(0), Err(0));
+        assert_eq!(deq.try_push_front(0), Err(0));
+        assert_eq!(buf(&deq), ptr);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut deq = BoundedSliceDeque::with_capacity(0);
+        assert!(deq.is_full());
+        assert_eq!(deq.push_back_overwrite(1), Some(1));
+        assert_eq!(deq.push_front_overwrite(2), Some(2));
+        assert_eq!(deq.try_push_back(3), Err(3));
+        assert!(deq.is_empty());
+    }
+
+    #[test]
+    fn overwrite_drops() {
+        let counter = Rc::new(());
+        {
+            let mut deq = BoundedSliceDeque::with_capacity(2);
+            for _ in 0..5 {
+                deq.push_back_overwrite(Rc::clone(&counter));
+            }
+            assert_eq!(Rc::strong_count(&counter), 3);
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn clone_keeps_capacity() {
+        let mut deq = BoundedSliceDeque::with_capacity(4);
+        deq.push_back_overwrite(1);
+        let mut c = deq.clone();
+        assert_eq!(c.capacity(), 4);
+        assert_eq!(c, deq);
+        for i in 0..4 {
+            c.push_back_overwrite(i);
+        }
+        assert_eq!(c, [0, 1, 2, 3]);
+    }
+}
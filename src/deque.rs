@@ -0,0 +1,260 @@
+//! A trait abstracting over double-ended queues.
+
+use super::*;
+
+#[cfg(feature = "use_std")]
+use std::collections::{vec_deque, VecDeque};
+
+/// A double-ended queue.
+///
+/// This trait allows writing code that works with both [`SliceDeque`] and
+/// `std::collections::VecDeque`, e.g., to fall back to `VecDeque` on
+/// platforms without mirrored memory support.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate slice_deque;
+/// # use slice_deque::{Deque, SliceDeque};
+/// fn rotate<D: Deque<i32>>(d: &mut D) {
+///     if let Some(v) = d.pop_front() {
+///         d.push_back(v);
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut a = sdeq![1, 2, 3];
+/// rotate(&mut a);
+/// assert_eq!(a, [2, 3, 1]);
+/// # }
+/// ```
+///
+/// [`SliceDeque`]: struct.SliceDeque.html
+pub trait Deque<T> {
+    /// Draining iterator returned by `drain`.
+    type Drain<'a>: DoubleEndedIterator<Item = T>
+    where
+        Self: 'a;
+
+    /// Prepends `value` to the deque.
+    fn push_front(&mut self, value: T);
+
+    /// Appends `value` to the deque.
+    fn push_back(&mut self, value: T);
+
+    /// Removes the first element and returns it, or `None` if the deque is
+    /// empty.
+    fn pop_front(&mut self) -> Option<T>;
+
+    /// Removes the last element and returns it, or `None` if the deque is
+    /// empty.
+    fn pop_back(&mut self) -> Option<T>;
+
+    /// Provides a reference to the first element, or `None` if the deque is
+    /// empty.
+    fn front(&self) -> Option<&T>;
+
+    /// Provides a reference to the last element, or `None` if the deque is
+    /// empty.
+    fn back(&self) -> Option<&T>;
+
+    /// Number of elements in the deque.
+    fn len(&self) -> usize;
+
+    /// Is the deque empty?
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements that the deque can hold without reallocating.
+    fn capacity(&self) -> usize;
+
+    /// Reserves capacity for inserting at least `additional` elements without
+    /// reallocating.
+    fn reserve(&mut self, additional: usize);
+
+    /// Removes the elements in `range` from the deque and returns them in an
+    /// iterator.
+    fn drain<R>(&mut self, range: R) -> Self::Drain<'_>
+    where
+        R: ops::RangeBounds<usize>;
+
+    /// Shortens the deque to `len` elements by removing elements from the
+    /// front.
+    fn truncate_front(&mut self, len: usize);
+
+    /// Shortens the deque to `len` elements by removing elements from the
+    /// back.
+    fn truncate_back(&mut self, len: usize);
+}
+
+impl<T> Deque<T> for SliceDeque<T> {
+    type Drain<'a>
+        = Drain<'a, T>
+    where
+        T: 'a;
+
+    #[inline]
+    fn push_front(&mut self, value: T) {
+        SliceDeque::push_front(self, value)
+    }
+    #[inline]
+    fn push_back(&mut self, value: T) {
+        SliceDeque::push_back(self, value)
+    }
+    #[inline]
+    fn pop_front(&mut self) -> Option<T> {
+        SliceDeque::pop_front(self)
+    }
+    #[inline]
+    fn pop_back(&mut self) -> Option<T> {
+        SliceDeque::pop_back(self)
+    }
+    #[inline]
+    fn front(&self) -> Option<&T> {
+        SliceDeque::front(self)
+    }
+    #[inline]
+    fn back(&self) -> Option<&T> {
+        SliceDeque::back(self)
+    }
+    #[inline]
+    fn len(&self) -> usize {
+        SliceDeque::len(self)
+    }
+    #[inline]
+    fn capacity(&self) -> usize {
+        SliceDeque::capacity(self)
+    }
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        SliceDeque::reserve(self, additional)
+    }
+    #[inline]
+    fn drain<R>(&mut self, range: R) -> Drain<'_, T>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        SliceDeque::drain(self, range)
+    }
+    #[inline]
+    fn truncate_front(&mut self, len: usize) {
+        SliceDeque::truncate_front(self, len)
+    }
+    #[inline]
+    fn truncate_back(&mut self, len: usize) {
+        SliceDeque::truncate_back(self, len)
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<T> Deque<T> for VecDeque<T> {
+    type Drain<'a>
+        = vec_deque::Drain<'a, T>
+    where
+        T: 'a;
+
+    #[inline]
+    fn push_front(&mut self, value: T) {
+        VecDeque::push_front(self, value)
+    }
+    #[inline]
+    fn push_back(&mut self, value: T) {
+        VecDeque::push_back(self, value)
+    }
+    #[inline]
+    fn pop_front(&mut self) -> Option<T> {
+        VecDeque::pop_front(self)
+    }
+    #[inline]
+    fn pop_back(&mut self) -> Option<T> {
+        VecDeque::pop_back(self)
+    }
+    #[inline]
+    fn front(&self) -> Option<&T> {
+        VecDeque::front(self)
+    }
+    #[inline]
+    fn back(&self) -> Option<&T> {
+        VecDeque::back(self)
+    }
+    #[inline]
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+    #[inline]
+    fn capacity(&self) -> usize {
+        VecDeque::capacity(self)
+    }
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        VecDeque::reserve(self, additional)
+    }
+    #[inline]
+    fn drain<R>(&mut self, range: R) -> vec_deque::Drain<'_, T>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        VecDeque::drain(self, range)
+    }
+    #[inline]
+    fn truncate_front(&mut self, len: usize) {
+        let cur_len = VecDeque::len(self);
+        if len < cur_len {
+            VecDeque::drain(self, ..cur_len - len);
+        }
+    }
+    #[inline]
+    fn truncate_back(&mut self, len: usize) {
+        VecDeque::truncate(self, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise<D: Deque<usize>>(d: &mut D) {
+        assert!(d.is_empty());
+        assert_eq!(d.pop_front(), None);
+        assert_eq!(d.pop_back(), None);
+        d.reserve(10);
+        assert!(d.capacity() >= 10);
+
+        for i in 0..10 {
+            d.push_back(i);
+            d.push_front(i);
+        }
+        assert_eq!(d.len(), 20);
+        assert_eq!(d.front(), Some(&9));
+        assert_eq!(d.back(), Some(&9));
+
+        let drained: Vec<_> = d.drain(8..12).collect();
+        assert_eq!(drained, [1, 0, 0, 1]);
+        assert_eq!(d.len(), 16);
+
+        d.truncate_front(12);
+        assert_eq!(d.front(), Some(&5));
+        d.truncate_back(8);
+        assert_eq!(d.back(), Some(&5));
+        d.truncate_front(20);
+        assert_eq!(d.len(), 8);
+
+        assert_eq!(d.pop_front(), Some(5));
+        assert_eq!(d.pop_back(), Some(5));
+        assert_eq!(d.drain(..).rev().next(), Some(4));
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn slice_deque() {
+        exercise(&mut SliceDeque::new());
+    }
+
+    #[test]
+    #[cfg(feature = "use_std")]
+    fn vec_deque() {
+        exercise(&mut VecDeque::new());
+    }
+}
@@ -0,0 +1,396 @@
+//! A queue of timestamped elements that expire.
+
+use super::*;
+
+use core::cell::Cell;
+
+/// A source of timestamps for an [`ExpiringDeque`].
+///
+/// The unit of time is up to the implementation, but it must be the same
+/// as the one of the timestamps passed to the methods of the deque.
+///
+/// [`ExpiringDeque`]: struct.ExpiringDeque.html
+pub trait Clock {
+    /// Current time.
+    fn now(&self) -> u64;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    #[inline]
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+/// A [`Clock`] measuring milliseconds since the UNIX epoch.
+///
+/// [`Clock`]: trait.Clock.html
+#[cfg(feature = "use_std")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "use_std")]
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> u64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// A [`Clock`] that only moves when told to, for tests.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::{Clock, ManualClock};
+/// let clock = ManualClock::new(10);
+/// clock.advance(5);
+/// assert_eq!(clock.now(), 15);
+/// clock.set(3);
+/// assert_eq!(clock.now(), 3);
+/// ```
+///
+/// [`Clock`]: trait.Clock.html
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    /// Current time.
+    now: Cell<u64>,
+}
+
+impl ManualClock {
+    /// Creates a clock whose current time is `now`.
+    #[inline]
+    pub fn new(now: u64) -> Self {
+        Self {
+            now: Cell::new(now),
+        }
+    }
+
+    /// Sets the current time to `now`.
+    #[inline]
+    pub fn set(&self, now: u64) {
+        self.now.set(now);
+    }
+
+    /// Moves the current time forward by `duration`.
+    #[inline]
+    pub fn advance(&self, duration: u64) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    #[inline]
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+/// A queue of elements paired with the time at which they were pushed.
+///
+/// Timestamps are non-decreasing from the front to the back, so the
+/// elements pushed since a given time are found by binary search and
+/// returned as a contiguous slice, and expired elements are removed from
+/// the front in bulk. Timestamps are either supplied by the caller with
+/// [`push_at`], or read from the clock `C` with [`push`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::{Clock, ExpiringDeque, ManualClock};
+/// let clock = ManualClock::new(0);
+/// let mut requests = ExpiringDeque::with_clock(&clock);
+/// for id in 0..5 {
+///     requests.push(id);
+///     clock.advance(10);
+/// }
+/// // Requests during the last 25 time units:
+/// assert_eq!(requests.iter_since(clock.now() - 25), &[3, 4]);
+/// assert_eq!(requests.expire_before(20), 2);
+/// assert_eq!(requests.as_slice(), &[2, 3, 4]);
+/// ```
+///
+/// [`push_at`]: #method.push_at
+/// [`push`]: #method.push
+pub struct ExpiringDeque<T, C> {
+    /// Elements of the queue.
+    values: SliceDeque<T>,
+    /// Time at which each element was pushed.
+    times: SliceDeque<u64>,
+    /// Clock used by `push`.
+    clock: C,
+}
+
+#[cfg(feature = "use_std")]
+impl<T> ExpiringDeque<T, SystemClock> {
+    /// Creates an empty queue that timestamps elements with the system
+    /// clock.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<T> Default for ExpiringDeque<T, SystemClock> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Clock> ExpiringDeque<T, C> {
+    /// Creates an empty queue that timestamps elements with `clock`.
+    #[inline]
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            values: SliceDeque::new(),
+            times: SliceDeque::new(),
+            clock,
+        }
+    }
+
+    /// Clock of the queue.
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Number of elements in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Is the queue empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Elements of the queue, from the oldest to the newest.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.values.as_slice()
+    }
+
+    /// Elements of the queue, from the oldest to the newest.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.values.as_mut_slice()
+    }
+
+    /// Timestamps of the elements, from the oldest to the newest.
+    #[inline]
+    pub fn timestamps(&self) -> &[u64] {
+        self.times.as_slice()
+    }
+
+    /// Returns the oldest element and its timestamp, or `None` if the queue
+    /// is empty.
+    #[inline]
+    pub fn front(&self) -> Option<(u64, &T)> {
+        Some((*self.times.front()?, self.values.front()?))
+    }
+
+    /// Returns the newest element and its timestamp, or `None` if the queue
+    /// is empty.
+    #[inline]
+    pub fn back(&self) -> Option<(u64, &T)> {
+        Some((*self.times.back()?, self.values.back()?))
+    }
+
+    /// Iterates over the timestamps and elements from the oldest to the
+    /// newest.
+    #[inline]
+    pub fn iter(&self) -> iter::Zip<slice::Iter<'_, u64>, slice::Iter<'_, T>> {
+        self.times.iter().zip(self.values.iter())
+    }
+
+    /// Appends `value` with the current time of the clock as its timestamp.
+    ///
+    /// If the clock went backwards since the last element was pushed, the
+    /// timestamp of the last element is used instead, so that timestamps
+    /// stay ordered.
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        let now = self.clock.now();
+        let now = self.times.back().map_or(now, |&last| cmp::max(now, last));
+        self.times.push_back(now);
+        self.values.push_back(value);
+    }
+
+    /// Appends `value` with timestamp `time`.
+    ///
+    /// # Panics
+    ///
+    /// If `time` is earlier than the timestamp of the newest element.
+    #[inline]
+    pub fn push_at(&mut self, time: u64, value: T) {
+        if let Some(&last) = self.times.back() {
+            assert!(
+                time >= last,
+                "time went backwards: {} is earlier than {}",
+                time,
+                last
+            );
+        }
+        self.times.push_back(time);
+        self.values.push_back(value);
+    }
+
+    /// Removes the oldest element and returns it with its timestamp, or
+    /// returns `None` if the queue is empty.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<(u64, T)> {
+        let time = self.times.pop_front()?;
+        let value = self.values.pop_front()?;
+        Some((time, value))
+    }
+
+    /// Removes the elements with a timestamp earlier than `time`.
+    ///
+    /// Returns the number of elements removed.
+    #[inline]
+    pub fn expire_before(&mut self, time: u64) -> usize {
+        let n = self.index_of(time);
+        let len = self.len() - n;
+        self.times.truncate_front(len);
+        self.values.truncate_front(len);
+        n
+    }
+
+    /// Removes the elements older than `age` according to the clock, that
+    /// is, with a timestamp earlier than `now - age`.
+    ///
+    /// Returns the number of elements removed.
+    #[inline]
+    pub fn expire_older_than(&mut self, age: u64) -> usize {
+        let now = self.clock.now();
+        self.expire_before(now.saturating_sub(age))
+    }
+
+    /// Returns the elements with a timestamp not earlier than `time`.
+    #[inline]
+    pub fn iter_since(&self, time: u64) -> &[T] {
+        &self.values[self.index_of(time)..]
+    }
+
+    /// Returns the elements with a timestamp not earlier than `time`
+    /// mutably.
+    #[inline]
+    pub fn iter_since_mut(&mut self, time: u64) -> &mut [T] {
+        let start = self.index_of(time);
+        &mut self.values[start..]
+    }
+
+    /// Removes all elements.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.times.clear();
+        self.values.clear();
+    }
+
+    /// Index of the first element with a timestamp not earlier than `time`.
+    #[inline]
+    fn index_of(&self, time: u64) -> usize {
+        self.times
+            .binary_search_by(|&t| {
+                if t < time {
+                    cmp::Ordering::Less
+                } else {
+                    cmp::Ordering::Greater
+                }
+            })
+            .unwrap_err()
+    }
+}
+
+impl<T: Clone, C: Clone> Clone for ExpiringDeque<T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            times: self.times.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for ExpiringDeque<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_map()
+            .entries(self.times.iter().zip(self.values.iter()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expire_and_since() {
+        let clock = ManualClock::new(100);
+        let mut q = ExpiringDeque::with_clock(&clock);
+        assert_eq!(q.expire_before(1000), 0);
+        assert_eq!(q.iter_since(0), &[] as &[u32]);
+        for i in 0..1000_u32 {
+            q.push(i);
+            if i % 3 == 0 {
+                clock.advance(1);
+            }
+        }
+        assert_eq!(q.front(), Some((100, &0)));
+        assert_eq!(q.back(), Some((433, &999)));
+        assert_eq!(q.iter_since(433), &[997, 998, 999]);
+        assert_eq!(q.iter_since(432).len(), 6);
+        assert_eq!(q.iter_since(500), &[] as &[u32]);
+        assert_eq!(q.iter_since(0).len(), 1000);
+        assert_eq!(q.expire_before(101), 1);
+        assert_eq!(q.expire_older_than(234), 297);
+        assert_eq!(q.front(), Some((200, &298)));
+        for (&t, &v) in q.iter() {
+            assert_eq!(t, 100 + (v as u64 + 2) / 3);
+        }
+        clock.advance(1000);
+        assert_eq!(q.expire_older_than(0), 702);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn clock_going_backwards() {
+        let clock = ManualClock::new(10);
+        let mut q = ExpiringDeque::with_clock(&clock);
+        q.push("a");
+        clock.set(5);
+        q.push("b");
+        q.push_at(12, "c");
+        assert_eq!(q.timestamps(), &[10, 10, 12]);
+        assert_eq!(q.pop_front(), Some((10, "a")));
+        assert_eq!(format!("{:?}", q), r#"{10: "b", 12: "c"}"#);
+        q.clear();
+        assert_eq!(q.pop_front(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_at_earlier_time() {
+        let mut q = ExpiringDeque::with_clock(ManualClock::new(0));
+        q.push_at(5, 1);
+        q.push_at(4, 2);
+    }
+
+    #[cfg(feature = "use_std")]
+    #[test]
+    fn system_clock() {
+        let mut q = ExpiringDeque::new();
+        q.push(1);
+        let (t, _) = q.front().unwrap();
+        assert!(t > 0 && t <= SystemClock.now());
+        assert_eq!(q.expire_older_than(u64::MAX), 0);
+    }
+}
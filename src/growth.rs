@@ -0,0 +1,216 @@
+//! Growth and shrink policies of a `SliceDeque`.
+
+use super::*;
+
+/// How the capacity of a deque grows when it is full.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Growth {
+    /// Doubles the capacity.
+    Double,
+    /// Grows the capacity by half of it.
+    OneAndAHalf,
+    /// Grows the capacity by a fixed number of allocation units.
+    Pages(usize),
+}
+
+/// Growth and shrink policy of a [`SliceDeque`].
+///
+/// The policy decides the capacity of the buffer that the deque allocates
+/// when it runs out of space, the maximum capacity that the deque may grow
+/// to, and whether the deque shrinks automatically when elements are
+/// removed.
+///
+/// Independently of the policy, the capacity is always rounded up to a
+/// multiple of the allocation granularity (the page size on most platforms).
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::{GrowthPolicy, SliceDeque};
+/// let mut deq = SliceDeque::new();
+/// deq.set_growth_policy(
+///     GrowthPolicy::pages(1).max_capacity(1 << 20).auto_shrink(4),
+/// );
+/// deq.extend(0_u8..100);
+/// # assert_eq!(deq.len(), 100);
+/// ```
+///
+/// [`SliceDeque`]: struct.SliceDeque.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GrowthPolicy {
+    /// How the capacity grows.
+    growth: Growth,
+    /// Maximum capacity requested when growing.
+    max_capacity: usize,
+    /// Shrink when `len < capacity / shrink_divisor`; `0` never shrinks.
+    shrink_divisor: usize,
+}
+
+impl GrowthPolicy {
+    /// Doubles the capacity when the deque is full.
+    ///
+    /// This is the default policy.
+    #[inline]
+    pub fn doubling() -> Self {
+        Self {
+            growth: Growth::Double,
+            max_capacity: usize::MAX,
+            shrink_divisor: 0,
+        }
+    }
+
+    /// Grows the capacity by 50% when the deque is full.
+    #[inline]
+    pub fn one_and_a_half() -> Self {
+        Self {
+            growth: Growth::OneAndAHalf,
+            ..Self::doubling()
+        }
+    }
+
+    /// Grows the capacity by `n` allocation units when the deque is full.
+    ///
+    /// The size of an allocation unit is the allocation granularity of the
+    /// platform, that is, the page size on most platforms.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    #[inline]
+    pub fn pages(n: usize) -> Self {
+        assert!(n > 0, "the deque must grow by at least one page");
+        Self {
+            growth: Growth::Pages(n),
+            ..Self::doubling()
+        }
+    }
+
+    /// Limits the capacity that the deque may grow to.
+    ///
+    /// Growing beyond `max_capacity` elements fails with
+    /// `AllocError::MaxCapacity`. Since the capacity is rounded up to the
+    /// allocation granularity, the deque may end up with a capacity slightly
+    /// larger than `max_capacity`.
+    #[inline]
+    pub fn max_capacity(self, max_capacity: usize) -> Self {
+        Self {
+            max_capacity,
+            ..self
+        }
+    }
+
+    /// Halves the capacity when the length of the deque drops below
+    /// `capacity / divisor` after removing elements.
+    ///
+    /// Shrinking is attempted by `pop_front`, `pop_back`, `truncate_front`,
+    /// `truncate_back`, `truncate`, `clear` and `drain`. If allocating the
+    /// smaller buffer fails, the deque keeps its current buffer.
+    ///
+    /// Shrinking moves the elements into a new buffer, so with this policy
+    /// removing elements invalidates raw pointers into the deque, like those
+    /// returned by `as_ptr`, just like adding elements does.
+    ///
+    /// # Panics
+    ///
+    /// If `divisor` is smaller than `3`, since then pushing and popping a
+    /// single element could reallocate the deque every time.
+    #[inline]
+    pub fn auto_shrink(self, divisor: usize) -> Self {
+        assert!(
+            divisor >= 3,
+            "the auto-shrink divisor must be at least 3, got {}",
+            divisor
+        );
+        Self {
+            shrink_divisor: divisor,
+            ..self
+        }
+    }
+
+    /// Maximum capacity that the deque may grow to.
+    #[inline]
+    pub(crate) fn max_capacity_limit(&self) -> usize {
+        self.max_capacity
+    }
+
+    /// Capacity to allocate for at least `req_cap` elements of `T` when the
+    /// current capacity is `cur_cap`.
+    ///
+    /// The result is at least `req_cap`, and only exceeds the maximum
+    /// capacity if `req_cap` does.
+    #[inline]
+    pub(crate) fn grow<T>(&self, cur_cap: usize, req_cap: usize) -> usize {
+        let new_cap = match self.growth {
+            Growth::Double => cur_cap.saturating_mul(2),
+            Growth::OneAndAHalf => cur_cap.saturating_add(cur_cap / 2),
+            Growth::Pages(n) => {
+                let elem_size = cmp::max(mem::size_of::<T>(), 1);
+                let per_page = cmp::max(
+                    mirrored::allocation_granularity() / elem_size,
+                    1,
+                );
+                cur_cap.saturating_add(per_page.saturating_mul(n))
+            }
+        };
+        cmp::max(req_cap, cmp::min(new_cap, self.max_capacity))
+    }
+
+    /// Capacity that a deque with `len` elements and capacity `cap` should
+    /// shrink to, if any.
+    #[inline]
+    pub(crate) fn shrink(&self, len: usize, cap: usize) -> Option<usize> {
+        if self.shrink_divisor != 0 && len < cap / self.shrink_divisor {
+            Some(cap / 2)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for GrowthPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::doubling()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grow() {
+        let p = GrowthPolicy::doubling();
+        assert_eq!(p.grow::<u32>(100, 101), 200);
+        assert_eq!(p.grow::<u32>(100, 300), 300);
+        assert_eq!(p.grow::<u32>(usize::MAX, 1), usize::MAX);
+
+        let p = GrowthPolicy::one_and_a_half();
+        assert_eq!(p.grow::<u32>(100, 101), 150);
+
+        let page = mirrored::allocation_granularity();
+        let p = GrowthPolicy::pages(2);
+        assert_eq!(p.grow::<u8>(page, page + 1), 3 * page);
+        assert_eq!(p.grow::<u16>(page, page + 1), 2 * page);
+        assert_eq!(p.grow::<()>(0, 1), 2 * page);
+
+        let p = GrowthPolicy::doubling().max_capacity(150);
+        assert_eq!(p.grow::<u32>(100, 101), 150);
+        assert_eq!(p.grow::<u32>(100, 160), 160);
+    }
+
+    #[test]
+    fn shrink() {
+        let p = GrowthPolicy::default();
+        assert_eq!(p.shrink(0, 100), None);
+        let p = p.auto_shrink(4);
+        assert_eq!(p.shrink(25, 100), None);
+        assert_eq!(p.shrink(24, 100), Some(50));
+    }
+
+    #[test]
+    #[should_panic]
+    fn shrink_divisor_too_small() {
+        let _ = GrowthPolicy::doubling().auto_shrink(2);
+    }
+}
@@ -0,0 +1,266 @@
+//! A queue that keeps a window of consumed elements for back-references.
+
+use super::*;
+
+/// A queue that keeps the last `window` consumed elements readable.
+///
+/// Elements popped from the front of a `HistoryBuffer` are not discarded
+/// but become part of its history, of which the last `window` elements are
+/// kept. Since the history and the pending elements are stored contiguously,
+/// [`copy_from_history`] can append back-references of the form
+/// `(distance, len)` as used by LZ77-style and delta decoders with plain
+/// memory copies.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::HistoryBuffer;
+/// let mut buf = HistoryBuffer::new(32 * 1024);
+/// buf.extend_from_slice(b"abc");
+/// // Back-reference: distance 3, length 7
+/// buf.copy_from_history(3, 7);
+/// assert_eq!(buf.consume(buf.len()), b"abcabcabca");
+/// assert!(buf.is_empty());
+/// assert_eq!(buf.history(), b"abcabcabca");
+/// // Back-references can reach into the history:
+/// buf.copy_from_history(4, 2);
+/// assert_eq!(buf.as_slice(), b"ab");
+/// ```
+///
+/// [`copy_from_history`]: #method.copy_from_history
+pub struct HistoryBuffer<T> {
+    /// History followed by the pending elements.
+    deq: SliceDeque<T>,
+    /// Number of elements of history at the front of `deq`.
+    ///
+    /// This can exceed `window` until the next mutation, so that the
+    /// elements returned by `consume` stay valid.
+    history_len: usize,
+    /// Maximum number of elements of history kept.
+    window: usize,
+}
+
+impl<T: Copy> HistoryBuffer<T> {
+    /// Creates an empty buffer that keeps the last `window` consumed
+    /// elements.
+    #[inline]
+    pub fn new(window: usize) -> Self {
+        Self {
+            deq: SliceDeque::new(),
+            history_len: 0,
+            window,
+        }
+    }
+
+    /// Maximum number of consumed elements kept in the history.
+    #[inline]
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Number of pending elements in the buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.deq.len() - self.history_len
+    }
+
+    /// Are there no pending elements in the buffer?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pending elements in the buffer.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.deq[self.history_len..]
+    }
+
+    /// Last consumed elements, from the oldest to the most recently consumed
+    /// one.
+    ///
+    /// At most `window()` elements are kept.
+    #[inline]
+    pub fn history(&self) -> &[T] {
+        let start = self.history_len.saturating_sub(self.window);
+        &self.deq[start..self.history_len]
+    }
+
+    /// Appends `value` to the pending elements.
+    #[inline]
+    pub fn push_back(&mut self, value: T) {
+        self.trim_history();
+        self.deq.push_back(value);
+    }
+
+    /// Appends the elements of `other` to the pending elements.
+    #[inline]
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.trim_history();
+        self.deq.extend_from_slice(other);
+    }
+
+    /// Consumes the first pending element and returns it, or returns `None`
+    /// if there are no pending elements.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.trim_history();
+        let value = *self.deq.get(self.history_len)?;
+        self.history_len += 1;
+        Some(value)
+    }
+
+    /// Consumes the first `n` pending elements and returns them.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than `n` pending elements.
+    #[inline]
+    pub fn consume(&mut self, n: usize) -> &[T] {
+        assert!(
+            n <= self.len(),
+            "cannot consume {} elements, only {} are pending",
+            n,
+            self.len()
+        );
+        self.trim_history();
+        let start = self.history_len;
+        self.history_len += n;
+        &self.deq[start..self.history_len]
+    }
+
+    /// Appends `len` elements copied starting `distance` elements before
+    /// the back of the buffer.
+    ///
+    /// The source range can reach into the history. If `len` is larger than
+    /// `distance`, the source overlaps with the elements being appended, that
+    /// is, the last `distance` elements are repeated.
+    ///
+    /// # Panics
+    ///
+    /// If `distance` is zero or larger than the number of elements of history
+    /// and pending elements.
+    #[inline]
+    pub fn copy_from_history(&mut self, distance: usize, len: usize) {
+        self.trim_history();
+        let total = self.deq.len();
+        assert!(
+            distance > 0 && distance <= total,
+            "back-reference distance {} out of range 1..={}",
+            distance,
+            total
+        );
+        self.deq.reserve(len);
+        unsafe {
+            let ptr = self.deq.as_mut_ptr();
+            let start = total - distance;
+            let mut end = total;
+            let mut remaining = len;
+            // Each copy doubles the length of the source, which stays a
+            // multiple of `distance`:
+            while remaining > 0 {
+                let n = cmp::min(remaining, end - start);
+                ptr::copy_nonoverlapping(ptr.add(start), ptr.add(end), n);
+                end += n;
+                remaining -= n;
+            }
+            self.deq.move_tail_unchecked(len as isize);
+        }
+    }
+
+    /// Removes the pending elements and the history.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.deq.clear();
+        self.history_len = 0;
+    }
+
+    /// Discards the history that does not fit in the window.
+    #[inline]
+    fn trim_history(&mut self) {
+        if self.history_len > self.window {
+            let excess = self.history_len - self.window;
+            let len = self.deq.len() - excess;
+            self.deq.truncate_front(len);
+            self.history_len = self.window;
+        }
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for HistoryBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("HistoryBuffer")
+            .field("history", &self.history())
+            .field("pending", &self.as_slice())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_window() {
+        let mut buf = HistoryBuffer::new(4);
+        buf.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(buf.pop_front(), Some(1));
+        assert_eq!(buf.history(), &[1]);
+        buf.extend_from_slice(&[4, 5, 6]);
+        assert_eq!(buf.consume(4), &[2, 3, 4, 5]);
+        assert_eq!(buf.history(), &[2, 3, 4, 5]);
+        assert_eq!(buf.as_slice(), &[6]);
+        assert_eq!(buf.pop_front(), Some(6));
+        assert_eq!(buf.pop_front(), None);
+        assert_eq!(buf.history(), &[3, 4, 5, 6]);
+        assert_eq!(buf.consume(0), &[] as &[i32]);
+
+        let mut buf = HistoryBuffer::new(2);
+        buf.extend_from_slice(&[1, 2, 3, 4, 5]);
+        assert_eq!(buf.consume(5), &[1, 2, 3, 4, 5]);
+        assert_eq!(buf.history(), &[4, 5]);
+        buf.copy_from_history(2, 1);
+        assert_eq!(buf.as_slice(), &[4]);
+    }
+
+    #[test]
+    fn back_references() {
+        let mut buf = HistoryBuffer::new(8);
+        buf.push_back(7_u8);
+        buf.copy_from_history(1, 5);
+        assert_eq!(buf.as_slice(), &[7; 6]);
+        buf.consume(6);
+        buf.extend_from_slice(&[1, 2]);
+        buf.copy_from_history(5, 3);
+        assert_eq!(buf.as_slice(), &[1, 2, 7, 7, 7]);
+        buf.copy_from_history(2, 0);
+        buf.copy_from_history(3, 8);
+        assert_eq!(buf.as_slice(), &[1, 2, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7]);
+        buf.clear();
+        assert!(buf.history().is_empty());
+    }
+
+    #[test]
+    fn back_reference_across_reallocation() {
+        let page = crate::mirrored::allocation_granularity();
+        let mut buf = HistoryBuffer::new(page);
+        let data: Vec<u8> = (0..page).map(|i| (i % 251) as u8).collect();
+        buf.extend_from_slice(&data);
+        buf.consume(page / 2);
+        buf.copy_from_history(page, page * 3);
+        let expected: Vec<u8> = data[page / 2..]
+            .iter()
+            .chain(data.iter().cycle().take(page * 3))
+            .cloned()
+            .collect();
+        assert_eq!(buf.as_slice(), &expected[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn distance_out_of_range() {
+        let mut buf = HistoryBuffer::new(8);
+        buf.extend_from_slice(&[1, 2]);
+        buf.copy_from_history(3, 1);
+    }
+}
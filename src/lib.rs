@@ -165,10 +165,53 @@ extern crate bytes;
 mod mirrored;
 pub use mirrored::{AllocError, Buffer};
 
+#[cfg(all(
+    any(target_os = "linux", target_os = "android", target_os = "openbsd"),
+    not(feature = "unix_sysv")
+))]
+pub use mirrored::{mapping_count, mapping_limit, set_mapping_limit};
+
+mod bounded;
+pub use bounded::BoundedSliceDeque;
+
+mod deque;
+pub use deque::Deque;
+
+mod seq;
+pub use seq::SeqDeque;
+
+mod growth;
+pub use growth::GrowthPolicy;
+
+mod window;
+pub use window::{Sample, SlidingWindow};
+
+mod history;
+pub use history::HistoryBuffer;
+
+mod string;
+pub use string::{CharDrain, FromUtf8Error, StrDeque};
+
+mod bits;
+pub use bits::{BitIter, BitSlice, BitSliceDeque};
+
+mod record;
+pub use record::{RecordDeque, Records};
+
+mod rows;
+pub use rows::{RowDeque, RowsView};
+
+mod expiring;
+#[cfg(feature = "use_std")]
+pub use expiring::SystemClock;
+pub use expiring::{Clock, ExpiringDeque, ManualClock};
+
 #[cfg(all(feature = "bytes_buf", feature = "use_std"))]
 use std::io;
 
-use core::{cmp, convert, fmt, hash, iter, mem, ops, ptr, slice, str};
+use core::{
+    any, cmp, convert, fmt, hash, iter, marker, mem, ops, ptr, slice, str,
+};
 
 use core::ptr::NonNull;
 
@@ -265,6 +308,8 @@ pub struct SliceDeque<T> {
     elems_: NonNull<[T]>,
     /// Mirrored memory buffer.
     buf: Buffer<T>,
+    /// Growth and shrink policy.
+    policy: GrowthPolicy,
 }
 
 // Safe because it is possible to free this from a different thread
@@ -332,11 +377,7 @@ pub use mem::forget as __mem_forget;
 #[macro_export]
 macro_rules! sdeq {
     ($elem:expr; $n:expr) => (
-        {
-            let mut deq = $crate::SliceDeque::with_capacity($n);
-            deq.resize($n, $elem);
-            deq
-        }
+        $crate::from_elem($elem, $n)
     );
     () => ( $crate::SliceDeque::new() );
     ($($x:expr),*) => (
@@ -370,6 +411,7 @@ impl<T> SliceDeque<T> {
             Self {
                 elems_: nonnull_raw_slice(buf.ptr(), 0),
                 buf,
+                policy: GrowthPolicy::default(),
             }
         }
     }
@@ -378,47 +420,78 @@ impl<T> SliceDeque<T> {
     ///
     /// The `ptr` must be a pointer to the beginning of the memory buffer from
     /// another `SliceDeque`, `capacity` the capacity of this `SliceDeque`, and
-    /// `elems` the elements of this `SliceDeque`.
+    /// `elems` the elements of this `SliceDeque`. See `into_raw_parts`.
     #[inline]
     pub unsafe fn from_raw_parts(
         ptr: *mut T, capacity: usize, elems: &mut [T],
     ) -> Self {
         let begin = elems.as_mut_ptr();
-        debug_assert!(in_bounds(slice::from_raw_parts(ptr, capacity), begin));
-        debug_assert!(elems.len() < capacity);
+        debug_assert!(
+            capacity == 0
+                || mem::size_of::<T>() == 0
+                || in_bounds(
+                    slice::from_raw_parts(
+                        ptr as *mut u8,
+                        Buffer::<T>::size_in_bytes(capacity * 2) / 2
+                    ),
+                    begin as *mut u8
+                )
+        );
+        debug_assert!(elems.len() <= capacity);
 
         Self {
             elems_: NonNull::new_unchecked(elems),
             buf: Buffer::from_raw_parts(ptr, capacity * 2),
+            policy: GrowthPolicy::default(),
         }
     }
 
-    /// Create an empty deque with capacity to hold `n` elements.
+    /// Attempts to create an empty deque with capacity to hold `n` elements.
     ///
     /// # Examples
     ///
     /// ```rust
     /// # use slice_deque::SliceDeque;
-    /// let deq = SliceDeque::with_capacity(10);
+    /// let deq = SliceDeque::try_with_capacity(10).unwrap();
+    /// assert!(deq.capacity() >= 10);
     /// # let o: SliceDeque<u32> = deq;
     /// ```
     #[inline]
-    pub fn with_capacity(n: usize) -> Self {
+    pub fn try_with_capacity(n: usize) -> Result<Self, AllocError> {
         unsafe {
-            let buf = Buffer::uninitialized(2 * n).unwrap_or_else(|e| {
-                let s = tiny_str!(
-                    "failed to allocate a buffer with capacity \"{}\" due to \"{:?}\"",
-                    n, e
-                );
-                panic!("{}", s.as_str())
-            });
-            Self {
+            let buf = Buffer::uninitialized(2 * n)?;
+            Ok(Self {
                 elems_: nonnull_raw_slice(buf.ptr(), 0),
                 buf,
-            }
+                policy: GrowthPolicy::default(),
+            })
         }
     }
 
+    /// Create an empty deque with capacity to hold `n` elements.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::SliceDeque;
+    /// let deq = SliceDeque::with_capacity(10);
+    /// # let o: SliceDeque<u32> = deq;
+    /// ```
+    #[inline]
+    pub fn with_capacity(n: usize) -> Self {
+        Self::try_with_capacity(n).unwrap_or_else(|e| {
+            let s = tiny_str!(
+                "failed to allocate a buffer with capacity \"{}\" due to \"{:?}\"",
+                n, e
+            );
+            panic!("{}", unsafe { s.as_str() })
+        })
+    }
+
     /// Returns the number of elements that the deque can hold without
     /// reallocating.
     ///
@@ -486,24 +559,14 @@ impl<T> SliceDeque<T> {
     /// of the deque and the second one is empty.
     #[inline]
     pub fn as_slices(&self) -> (&[T], &[T]) {
-        unsafe {
-            let left = self.as_slice();
-            let right =
-                slice::from_raw_parts(usize::max_value() as *const _, 0);
-            (left, right)
-        }
+        (self.as_slice(), &[])
     }
 
     /// Returns a pair of slices, where the first slice contains the contents
     /// of the deque and the second one is empty.
     #[inline]
     pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
-        unsafe {
-            let left = self.as_mut_slice();
-            let right =
-                slice::from_raw_parts_mut(usize::max_value() as *mut _, 0);
-            (left, right)
-        }
+        (self.as_mut_slice(), &mut [])
     }
 
     /// Returns the slice of uninitialized memory between the `tail` and the
@@ -538,6 +601,207 @@ impl<T> SliceDeque<T> {
         slice::from_raw_parts_mut(ptr as _, self.capacity() - self.len())
     }
 
+    /// Returns the spare capacity of the deque, that is, the uninitialized
+    /// memory after the back of the deque.
+    ///
+    /// After writing to the first `n` elements of the slice, they can be
+    /// added to the back of the deque with `commit_back(n)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use std::mem::MaybeUninit;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3];
+    /// let spare = deq.spare_back_mut();
+    /// spare[0] = MaybeUninit::new(4);
+    /// spare[1] = MaybeUninit::new(5);
+    /// unsafe { deq.commit_back(2) };
+    /// assert_eq!(deq, [1, 2, 3, 4, 5]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn spare_back_mut(&mut self) -> &mut [mem::MaybeUninit<T>] {
+        unsafe { self.tail_head_slice() }
+    }
+
+    /// Returns the spare capacity of the deque, that is, the uninitialized
+    /// memory before the front of the deque.
+    ///
+    /// Because the buffer is mirrored this memory overlaps with that of
+    /// `spare_back_mut`. After writing to the last `n` elements of the slice,
+    /// they can be added to the front of the deque with `commit_front(n)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use std::mem::MaybeUninit;
+    /// # fn main() {
+    /// let mut deq = sdeq![3, 4, 5];
+    /// let spare = deq.spare_front_mut();
+    /// let n = spare.len();
+    /// spare[n - 2] = MaybeUninit::new(1);
+    /// spare[n - 1] = MaybeUninit::new(2);
+    /// unsafe { deq.commit_front(2) };
+    /// assert_eq!(deq, [1, 2, 3, 4, 5]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn spare_front_mut(&mut self) -> &mut [mem::MaybeUninit<T>] {
+        unsafe {
+            let len = self.capacity() - self.len();
+            let begin = self.before_head(len) as *mut mem::MaybeUninit<T>;
+            slice::from_raw_parts_mut(begin, len)
+        }
+    }
+
+    /// Adds the first `n` elements of `spare_back_mut()` to the back of the
+    /// deque.
+    ///
+    /// # Safety
+    ///
+    /// The elements must have been initialized, and `n` must not be larger
+    /// than `capacity() - len()`.
+    ///
+    /// If `-C debug-assertions=1` violating this pre-condition `panic!`s.
+    #[inline]
+    pub unsafe fn commit_back(&mut self, n: usize) {
+        debug_assert!(n <= self.capacity() - self.len());
+        self.move_tail_unchecked(n as isize);
+    }
+
+    /// Adds the last `n` elements of `spare_front_mut()` to the front of the
+    /// deque.
+    ///
+    /// # Safety
+    ///
+    /// The elements must have been initialized, and `n` must not be larger
+    /// than `capacity() - len()`.
+    ///
+    /// If `-C debug-assertions=1` violating this pre-condition `panic!`s.
+    #[inline]
+    pub unsafe fn commit_front(&mut self, n: usize) {
+        debug_assert!(n <= self.capacity() - self.len());
+        self.move_head_unchecked(-(n as isize));
+    }
+
+    /// Passes a `SpareWriter` over the spare capacity at the back of the
+    /// deque to `f`, and adds the elements written through it to the back of
+    /// the deque.
+    ///
+    /// This allows filling the deque directly, e.g., with a `read` system
+    /// call. Use `reserve` first to make sure that there is enough spare
+    /// capacity. Returns the result of `f`.
+    ///
+    /// The elements written are added even if `f` panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut src = &b"world"[..];
+    /// let mut deq = sdeq![b'h', b'e', b'l', b'l', b'o', b' '];
+    /// deq.reserve(src.len());
+    /// let n = deq.write_back_with(|w| {
+    ///     // Like `read(2)`: copy as many bytes as fit.
+    ///     let n = w.extend_from_slice(src);
+    ///     src = &src[n..];
+    ///     n
+    /// });
+    /// assert_eq!(n, 5);
+    /// assert_eq!(deq, b"hello world");
+    /// # }
+    /// ```
+    #[inline]
+    pub fn write_back_with<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut SpareWriter<T>) -> R,
+    {
+        f(&mut SpareWriter {
+            written: 0,
+            deq: self,
+        })
+    }
+
+    /// Starts a speculative sequence of `push_back` and `pop_front` calls
+    /// that can be undone.
+    ///
+    /// The elements popped through the returned `Checkpoint` are not dropped
+    /// but only hidden, until the checkpoint is committed. Rolling back the
+    /// checkpoint, or dropping it, restores the popped elements and drops the
+    /// pushed ones.
+    ///
+    /// Only pushes and pops are undone: elements modified in place through
+    /// the checkpoint keep their new values after a rollback.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut deq = sdeq![b'G', b'E', b'T', b' '];
+    /// {
+    ///     let mut cp = deq.checkpoint();
+    ///     assert_eq!(cp.pop_front(), Some(&b'G'));
+    ///     assert_eq!(cp.pop_front(), Some(&b'E'));
+    ///     assert_eq!(cp, [b'T', b' ']);
+    ///     // Incomplete message: restore the consumed bytes.
+    ///     cp.rollback();
+    /// }
+    /// assert_eq!(deq, b"GET ");
+    ///
+    /// let mut cp = deq.checkpoint();
+    /// cp.pop_front();
+    /// cp.push_back(b'/');
+    /// cp.commit();
+    /// assert_eq!(deq, b"ET /");
+    /// # }
+    /// ```
+    #[inline]
+    pub fn checkpoint(&mut self) -> Checkpoint<'_, T> {
+        Checkpoint {
+            consumed: 0,
+            len: self.len(),
+            deq: self,
+        }
+    }
+
+    /// Returns the contents of the deque as a slice, together with its
+    /// spare capacity.
+    ///
+    /// See `spare_back_mut`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use std::mem::MaybeUninit;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2];
+    /// let (elems, spare) = deq.split_at_spare_mut();
+    /// spare[0] = MaybeUninit::new(elems[0] + elems[1]);
+    /// unsafe { deq.commit_back(1) };
+    /// assert_eq!(deq, [1, 2, 3]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn split_at_spare_mut(
+        &mut self,
+    ) -> (&mut [T], &mut [mem::MaybeUninit<T>]) {
+        unsafe {
+            let len = self.len();
+            let spare = self.capacity() - len;
+            let ptr = self.as_mut_ptr();
+            (
+                slice::from_raw_parts_mut(ptr, len),
+                slice::from_raw_parts_mut(ptr.add(len) as *mut _, spare),
+            )
+        }
+    }
+
     /// Attempts to reserve capacity for inserting at least `additional`
     /// elements without reallocating. Does nothing if the capacity is already
     /// sufficient.
@@ -577,31 +841,98 @@ impl<T> SliceDeque<T> {
     fn reserve_capacity(
         &mut self, new_capacity: usize,
     ) -> Result<(), AllocError> {
-        unsafe {
-            if new_capacity <= self.capacity() {
-                return Ok(());
-            }
+        if new_capacity <= self.capacity() {
+            return Ok(());
+        }
+        let len = self.len();
+        unsafe { self.reallocate_with_gap(new_capacity, len, len, 0) }
+    }
 
-            let mut new_buffer = Buffer::uninitialized(2 * new_capacity)?;
-            debug_assert!(new_buffer.len() >= 2 * new_capacity);
+    /// Moves the first `len` elements of the deque to a new buffer with
+    /// capacity for at least `new_capacity` elements, leaving a gap of
+    /// `gap_len` uninitialized elements at `gap_index`.
+    ///
+    /// The elements at `gap_index..len` are moved `gap_len` positions
+    /// towards the back. `len` can be larger than `self.len()` and the length
+    /// of the deque is not modified.
+    ///
+    /// # Unsafe
+    ///
+    /// The first `len` elements of the buffer must be within capacity, and
+    /// `gap_index <= len` and `len + gap_len <= new_capacity` must hold.
+    unsafe fn reallocate_with_gap(
+        &mut self, new_capacity: usize, len: usize, gap_index: usize,
+        gap_len: usize,
+    ) -> Result<(), AllocError> {
+        debug_assert!(len <= self.capacity());
+        debug_assert!(gap_index <= len);
+        debug_assert!(len + gap_len <= new_capacity);
 
-            let len = self.len();
-            // Move the elements from the current buffer
-            // to the beginning of the new buffer:
-            {
-                let from_ptr = self.as_mut_ptr();
-                let to_ptr = new_buffer.as_mut_slice().as_mut_ptr();
-                crate::ptr::copy_nonoverlapping(from_ptr, to_ptr, len);
-            }
+        if new_capacity > self.capacity()
+            && new_capacity > self.policy.max_capacity_limit()
+        {
+            return Err(AllocError::MaxCapacity);
+        }
 
-            // Exchange buffers
-            crate::mem::swap(&mut self.buf, &mut new_buffer);
+        let mut new_buffer = Buffer::uninitialized(2 * new_capacity)?;
+        debug_assert!(new_buffer.len() >= 2 * new_capacity);
 
-            // Correct the slice - we copied to the
-            // beginning of the of the new buffer:
-            self.elems_ = nonnull_raw_slice(self.buf.ptr(), len);
-            Ok(())
+        let old_len = self.len();
+        // Move the elements from the current buffer
+        // to the beginning of the new buffer, around the gap:
+        {
+            let from_ptr = self.as_mut_ptr();
+            let to_ptr = new_buffer.as_mut_slice().as_mut_ptr();
+            crate::ptr::copy_nonoverlapping(from_ptr, to_ptr, gap_index);
+            crate::ptr::copy_nonoverlapping(
+                from_ptr.add(gap_index),
+                to_ptr.add(gap_index + gap_len),
+                len - gap_index,
+            );
+        }
+
+        // Exchange buffers
+        crate::mem::swap(&mut self.buf, &mut new_buffer);
+
+        // Correct the slice - we copied to the
+        // beginning of the of the new buffer:
+        self.elems_ = nonnull_raw_slice(self.buf.ptr(), old_len);
+        Ok(())
+    }
+
+    /// Opens a gap of `gap_len` uninitialized elements at `index`, growing
+    /// the deque if necessary.
+    ///
+    /// If the deque does not need to grow, either the elements before or
+    /// after `index` are shifted, whichever are fewer. Otherwise, the gap is
+    /// left while moving the elements to the new buffer. The length of the
+    /// deque increases by `gap_len`.
+    ///
+    /// # Unsafe
+    ///
+    /// The caller must initialize the gap. `index` must be in range
+    /// `[0, len()]`.
+    unsafe fn open_gap(
+        &mut self, index: usize, gap_len: usize,
+    ) -> Result<(), AllocError> {
+        let len = self.len();
+        debug_assert!(index <= len);
+        if intrinsics::unlikely(len + gap_len > self.capacity()) {
+            let new_cap = self.grow_policy(gap_len);
+            self.reallocate_with_gap(new_cap, len, index, gap_len)?;
+            self.move_tail_unchecked(gap_len as isize);
+        } else if index > len / 2 {
+            // Shift elements towards the back
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(gap_len), len - index);
+            self.move_tail_unchecked(gap_len as isize);
+        } else {
+            // Shift elements towards the front
+            self.move_head_unchecked(-(gap_len as isize));
+            let p = self.as_mut_ptr();
+            ptr::copy(p.add(gap_len), p, index);
         }
+        Ok(())
     }
 
     /// Reserves the minimum capacity for exactly `additional` more elements to
@@ -635,21 +966,64 @@ impl<T> SliceDeque<T> {
         debug_assert!(self.capacity() >= old_len + additional);
     }
 
-    /// Growth policy of the deque. The capacity is going to be a multiple of
-    /// the page-size anyways, so we just double capacity when needed.
+    /// Capacity required for inserting `additional` elements, according to
+    /// the growth policy of the deque.
     #[inline]
     fn grow_policy(&self, additional: usize) -> usize {
         let cur_cap = self.capacity();
         let old_len = self.len();
         let req_cap = old_len.checked_add(additional).expect("overflow");
         if req_cap > cur_cap {
-            let dbl_cap = cur_cap.saturating_mul(2);
-            cmp::max(req_cap, dbl_cap)
+            self.policy.grow::<T>(cur_cap, req_cap)
         } else {
             req_cap
         }
     }
 
+    /// Returns the growth and shrink policy of the deque.
+    #[inline]
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.policy
+    }
+
+    /// Sets the growth and shrink policy of the deque.
+    ///
+    /// The new policy applies to the next reallocation; the current buffer is
+    /// kept even if it does not satisfy the policy.
+    ///
+    /// With a policy that [shrinks automatically], removing elements can
+    /// reallocate the deque and invalidate pointers into it.
+    ///
+    /// [shrinks automatically]: struct.GrowthPolicy.html#method.auto_shrink
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::{GrowthPolicy, SliceDeque};
+    /// let mut deq = SliceDeque::new();
+    /// deq.set_growth_policy(GrowthPolicy::doubling().max_capacity(4));
+    /// deq.extend(0..4);
+    /// let cap = deq.capacity();
+    /// assert!(deq.try_reserve(cap - 4).is_ok());
+    /// assert!(deq.try_reserve(cap - 3).is_err());
+    /// # let o: SliceDeque<u32> = deq;
+    /// ```
+    #[inline]
+    pub fn set_growth_policy(&mut self, policy: GrowthPolicy) {
+        self.policy = policy;
+    }
+
+    /// Shrinks the deque if its growth policy asks for it.
+    #[inline]
+    fn auto_shrink(&mut self) {
+        if let Some(new_cap) = self.policy.shrink(self.len(), self.capacity())
+        {
+            // Shrinking is an optimization: keep the current buffer if
+            // allocating the new one fails.
+            let _ = self.shrink_capacity(new_cap);
+        }
+    }
+
     /// Moves the deque head by `x`.
     ///
     /// # Panics
@@ -782,17 +1156,78 @@ impl<T> SliceDeque<T> {
     /// Appends elements to `self` from `other`.
     #[inline]
     unsafe fn append_elements(&mut self, other: *const [T]) {
-        let count = (*other).len();
+        let count = (&*other).len();
         self.reserve(count);
         let len = self.len();
         ptr::copy_nonoverlapping(
             other as *const T,
-            self.get_unchecked_mut(len),
+            self.as_mut_ptr().add(len),
             count,
         );
         self.move_tail_unchecked(count as isize);
     }
 
+    /// Creates a deque from the elements of `iterator`.
+    ///
+    /// A common case is passing a deque into a function which immediately
+    /// re-collects into a deque. If the `IntoIter` has not been advanced at
+    /// all, its buffer is reused.
+    fn from_into_iter(mut iterator: IntoIter<T>) -> Self {
+        if ptr::eq(iterator.buf.as_ptr(), iterator.ptr) {
+            unsafe {
+                let deq = Self::from_raw_parts(
+                    iterator.buf.as_ptr(),
+                    iterator.cap,
+                    iterator.elems(),
+                );
+                #[allow(clippy::mem_forget)]
+                mem::forget(iterator);
+                deq
+            }
+        } else {
+            let mut deque = Self::new();
+            deque.extend_from_into_iter(iterator);
+            deque
+        }
+    }
+
+    /// Moves the remaining elements of `iterator` to the back of `self` with
+    /// a single `memcpy`.
+    fn extend_from_into_iter(&mut self, mut iterator: IntoIter<T>) {
+        unsafe {
+            self.append_elements(iterator.as_slice() as _);
+        }
+        iterator.ptr = iterator.end;
+    }
+
+    /// Prepends elements to `self` from `other`.
+    #[inline]
+    unsafe fn prepend_elements(&mut self, other: *const [T]) {
+        let count = (&*other).len();
+        self.reserve(count);
+        let dst = self.before_head(count);
+        ptr::copy_nonoverlapping(other as *const T, dst, count);
+        self.move_head_unchecked(-(count as isize));
+    }
+
+    /// Returns a pointer to the slot `n` elements before the front of the
+    /// deque.
+    ///
+    /// That slot is only `capacity() - n` elements after the front if the
+    /// elements fill the buffer exactly, so it is computed from the end of
+    /// the memory region that starts at the front instead.
+    ///
+    /// # Safety
+    ///
+    /// `n` must not be larger than `capacity() - len()`.
+    #[inline]
+    unsafe fn before_head(&mut self, n: usize) -> *mut T {
+        debug_assert!(n <= self.capacity() - self.len());
+        let region_size = Buffer::<T>::size_in_bytes(self.buf.len()) / 2;
+        let end = (self.as_mut_ptr() as *mut u8).add(region_size) as *mut T;
+        end.sub(n)
+    }
+
     /// Steal the elements from the slice `s`. You should `mem::forget` the
     /// slice afterwards.
     pub unsafe fn steal_from_slice(s: &[T]) -> Self {
@@ -801,6 +1236,31 @@ impl<T> SliceDeque<T> {
         deq
     }
 
+    /// Attempts to move all the elements of `other` into `Self`, leaving
+    /// `other` empty.
+    ///
+    /// On failure both `self` and `other` are left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3];
+    /// let mut deq2 = sdeq![4, 5, 6];
+    /// deq.try_append(&mut deq2).unwrap();
+    /// assert_eq!(deq, [1, 2, 3, 4, 5, 6]);
+    /// assert_eq!(deq2, []);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn try_append(&mut self, other: &mut Self) -> Result<(), AllocError> {
+        self.try_reserve(other.len())?;
+        self.append(other);
+        Ok(())
+    }
+
     /// Moves all the elements of `other` into `Self`, leaving `other` empty.
     ///
     /// # Panics
@@ -828,8 +1288,96 @@ impl<T> SliceDeque<T> {
         }
     }
 
-    /// Provides a reference to the first element, or `None` if the deque is
-    /// empty.
+    /// Moves all the elements of `other` to the front of `Self`, preserving
+    /// their order and leaving `other` empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of elements in the deque overflows a `isize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![4, 5, 6];
+    /// let mut deq2 = sdeq![1, 2, 3];
+    /// deq.prepend(&mut deq2);
+    /// assert_eq!(deq, [1, 2, 3, 4, 5, 6]);
+    /// assert_eq!(deq2, []);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn prepend(&mut self, other: &mut Self) {
+        unsafe {
+            self.prepend_elements(other.as_slice() as _);
+            other.elems_ = nonnull_raw_slice(other.buf.ptr(), 0);
+        }
+    }
+
+    /// Prepends the elements of `iter` to the deque, preserving their order.
+    ///
+    /// The elements are written in front of the head, which is then moved
+    /// only once. If the lower bound of the iterator's `size_hint()` is not
+    /// exact, the remaining elements are collected into a temporary deque,
+    /// and then moved in front of the deque at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of elements in the deque overflows a `isize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![4, 5, 6];
+    /// deq.extend_front(1..4);
+    /// assert_eq!(deq, [1, 2, 3, 4, 5, 6]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn extend_front<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+        let (lower_bound, _upper_bound) = iter.size_hint();
+        self.reserve(lower_bound);
+        let mut count = 0;
+        unsafe {
+            // The elements are leaked if `next` panics, since the head is
+            // only moved once all of them have been written:
+            let dst = self.before_head(lower_bound);
+            while count < lower_bound {
+                if let Some(v) = iter.next() {
+                    ptr::write(dst.add(count), v);
+                    count += 1;
+                } else {
+                    // `size_hint` lied: move the elements next to the head
+                    ptr::copy(dst, dst.add(lower_bound - count), count);
+                    break;
+                }
+            }
+            self.move_head_unchecked(-(count as isize));
+        }
+        // Insert the remaining elements, if any, after the prepended ones:
+        let mut rest: Self = iter.collect();
+        let extra = rest.len();
+        if extra == 0 {
+            return;
+        }
+        self.reserve(extra);
+        unsafe {
+            self.move_head_unchecked(-(extra as isize));
+            let dst = self.as_mut_ptr();
+            ptr::copy(dst.add(extra), dst, count);
+            ptr::copy_nonoverlapping(rest.as_ptr(), dst.add(count), extra);
+            rest.elems_ = nonnull_raw_slice(rest.buf.ptr(), 0);
+        }
+    }
+
+    /// Provides a reference to the first element, or `None` if the deque is
+    /// empty.
     ///
     /// # Examples
     ///
@@ -1013,6 +1561,12 @@ impl<T> SliceDeque<T> {
     /// Removes the first element and returns it, or `None` if the deque is
     /// empty.
     ///
+    /// If the growth policy of the deque [shrinks automatically], this can
+    /// move the remaining elements into a smaller buffer, which invalidates
+    /// pointers into the deque.
+    ///
+    /// [shrinks automatically]: struct.GrowthPolicy.html#method.auto_shrink
+    ///
     /// # Examples
     ///
     /// ```
@@ -1035,6 +1589,7 @@ impl<T> SliceDeque<T> {
                 Some(v) => ptr::read(v),
             };
             self.move_head_unchecked(1);
+            self.auto_shrink();
             Some(v)
         }
     }
@@ -1042,6 +1597,12 @@ impl<T> SliceDeque<T> {
     /// Removes the last element from the deque and returns it, or `None` if it
     /// is empty.
     ///
+    /// If the growth policy of the deque [shrinks automatically], this can
+    /// move the remaining elements into a smaller buffer, which invalidates
+    /// pointers into the deque.
+    ///
+    /// [shrinks automatically]: struct.GrowthPolicy.html#method.auto_shrink
+    ///
     /// # Examples
     ///
     /// ```
@@ -1065,6 +1626,7 @@ impl<T> SliceDeque<T> {
                 Some(v) => ptr::read(v),
             };
             self.move_tail_unchecked(-1);
+            self.auto_shrink();
             Some(v)
         }
     }
@@ -1092,22 +1654,72 @@ impl<T> SliceDeque<T> {
         if unsafe { intrinsics::unlikely(self.is_empty()) } {
             return;
         }
+        self.shrink_to(0);
+    }
 
-        // FIXME: we should compute the capacity and only allocate a shrunk
-        // deque if that's worth it.
-        let mut new_sdeq = Self::with_capacity(self.len());
-        if new_sdeq.capacity() < self.capacity() {
-            unsafe {
-                crate::ptr::copy_nonoverlapping(
-                    self.as_mut_ptr(),
-                    new_sdeq.as_mut_ptr(),
-                    self.len(),
-                );
-                new_sdeq.elems_ =
-                    nonnull_raw_slice(new_sdeq.buf.ptr(), self.len());
-                mem::swap(self, &mut new_sdeq);
-            }
+    /// Shrinks the capacity of the deque with a lower bound.
+    ///
+    /// The capacity will remain at least as large as both the length and
+    /// `min_capacity`, rounded up to a multiple of the page size. If the
+    /// current capacity is less than the lower limit, this is a no-op.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::SliceDeque;
+    /// let mut deq = SliceDeque::with_capacity(100_000);
+    /// deq.extend(0..4);
+    /// deq.shrink_to(1000);
+    /// assert!(deq.capacity() >= 1000 && deq.capacity() < 100_000);
+    /// # let o: SliceDeque<u32> = deq;
+    /// ```
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let new_cap = cmp::max(self.len(), min_capacity);
+        self.shrink_capacity(new_cap).unwrap();
+    }
+
+    /// Moves the elements to a smaller buffer with capacity for at least
+    /// `new_capacity` elements, if that actually allocates less memory.
+    fn shrink_capacity(
+        &mut self, new_capacity: usize,
+    ) -> Result<(), AllocError> {
+        let len = self.len();
+        debug_assert!(new_capacity >= len);
+        if mem::size_of::<T>() == 0 || new_capacity >= self.capacity() {
+            return Ok(());
+        }
+        // Buffers are allocated in multiples of the allocation granularity,
+        // so only an empty buffer or one with fewer allocation units is
+        // smaller:
+        if new_capacity != 0
+            && Buffer::<T>::size_in_bytes(2 * new_capacity)
+                >= Buffer::<T>::size_in_bytes(self.buf.len())
+        {
+            return Ok(());
         }
+        unsafe { self.reallocate_with_gap(new_capacity, len, len, 0) }
+    }
+
+    /// Returns the elements of the deque as a slice.
+    ///
+    /// The elements of a `SliceDeque` are always contiguous in memory, so
+    /// this never moves any elements. It is provided for compatibility with
+    /// `VecDeque`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut deq = sdeq![2, 3];
+    /// deq.push_front(1);
+    /// assert_eq!(deq.make_contiguous(), &[1, 2, 3]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.as_mut_slice()
     }
 
     /// Shortens the deque by removing excess elements from the back.
@@ -1115,6 +1727,12 @@ impl<T> SliceDeque<T> {
     /// If `len` is greater than the SliceDeque's current length, this has no
     /// effect.
     ///
+    /// If the growth policy of the deque [shrinks automatically], this can
+    /// move the remaining elements into a smaller buffer, which invalidates
+    /// pointers into the deque.
+    ///
+    /// [shrinks automatically]: struct.GrowthPolicy.html#method.auto_shrink
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -1142,12 +1760,14 @@ impl<T> SliceDeque<T> {
             ptr::drop_in_place(&mut *s);
             debug_assert_eq!(self.len(), len);
         }
+        self.auto_shrink();
     }
 
     /// Shortens the deque by removing excess elements from the back.
     ///
     /// If `len` is greater than the SliceDeque's current length, this has no
-    /// effect. See `truncate_back` for examples.
+    /// effect. Like `truncate_back`, this can reallocate the deque if its
+    /// growth policy shrinks automatically. See `truncate_back` for examples.
     #[inline]
     pub fn truncate(&mut self, len: usize) {
         self.truncate_back(len);
@@ -1158,6 +1778,12 @@ impl<T> SliceDeque<T> {
     /// If `len` is greater than the SliceDeque's current length, this has no
     /// effect.
     ///
+    /// If the growth policy of the deque [shrinks automatically], this can
+    /// move the remaining elements into a smaller buffer, which invalidates
+    /// pointers into the deque.
+    ///
+    /// [shrinks automatically]: struct.GrowthPolicy.html#method.auto_shrink
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -1185,6 +1811,34 @@ impl<T> SliceDeque<T> {
             ptr::drop_in_place(&mut *s);
             debug_assert_eq!(self.len(), len);
         }
+        self.auto_shrink();
+    }
+
+    /// Returns the start and end indices of `range` within the deque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the deque.
+    #[inline]
+    fn range_bounds<R>(&self, range: R) -> (usize, usize)
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        use ops::Bound::{Excluded, Included, Unbounded};
+        let start = match range.start_bound() {
+            Included(&n) => n,
+            Excluded(&n) => n + 1,
+            Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Included(&n) => n + 1,
+            Excluded(&n) => n,
+            Unbounded => self.len(),
+        };
+        assert!(start <= end);
+        assert!(end <= self.len());
+        (start, end)
     }
 
     /// Creates a draining iterator that removes the specified range in the
@@ -1196,6 +1850,16 @@ impl<T> SliceDeque<T> {
     /// Note 2: It is unspecified how many elements are removed from the deque
     /// if the `Drain` value is leaked.
     ///
+    /// Note 3: The hole left by the removed range is closed by moving either
+    /// the elements before or after `range`, whichever are fewer. Draining a
+    /// range at the front of the deque only moves the head of the deque.
+    ///
+    /// Note 4: If the growth policy of the deque [shrinks automatically],
+    /// dropping the `Drain` can move the remaining elements into a smaller
+    /// buffer, which invalidates pointers into the deque.
+    ///
+    /// [shrinks automatically]: struct.GrowthPolicy.html#method.auto_shrink
+    ///
     /// # Panics
     ///
     /// Panics if the starting point is greater than the end point or if
@@ -1223,7 +1887,6 @@ impl<T> SliceDeque<T> {
     where
         R: ops::RangeBounds<usize>,
     {
-        use ops::Bound::{Excluded, Included, Unbounded};
         // Memory safety
         //
         // When the Drain is first created, it shortens the length of
@@ -1232,22 +1895,12 @@ impl<T> SliceDeque<T> {
         // never gets to run.
         //
         // Drain will ptr::read out the values to remove.
-        // When finished, remaining tail of the deque is copied back to cover
-        // the hole, and the deque length is restored to the new length.
+        // When finished, the shorter of the remaining head and tail of the
+        // deque is copied over the hole, and the deque head and length are
+        // restored.
         //
         let len = self.len();
-        let start = match range.start_bound() {
-            Included(&n) => n,
-            Excluded(&n) => n + 1,
-            Unbounded => 0,
-        };
-        let end = match range.end_bound() {
-            Included(&n) => n + 1,
-            Excluded(&n) => n,
-            Unbounded => len,
-        };
-        assert!(start <= end);
-        assert!(end <= len);
+        let (start, end) = self.range_bounds(range);
 
         unsafe {
             // set self.deq length's to start, to be safe in case Drain is
@@ -1345,6 +1998,41 @@ impl<T> SliceDeque<T> {
         }
     }
 
+    /// Attempts to insert an `element` at `index` within the deque, shifting
+    /// all elements with indices greater than or equal to `index` towards the
+    /// back.
+    ///
+    /// On failure the deque is left unchanged and `element` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than deque's length
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq!['a', 'b', 'c'];
+    /// deq.try_insert(1, 'd').unwrap();
+    /// assert_eq!(deq, &['a', 'd', 'b', 'c']);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn try_insert(
+        &mut self, index: usize, element: T,
+    ) -> Result<(), (T, AllocError)> {
+        assert!(index <= self.len());
+        unsafe {
+            if let Err(e) = self.open_gap(index, 1) {
+                return Err((element, e));
+            }
+            ptr::write(self.as_mut_ptr().add(index), element);
+            Ok(())
+        }
+    }
+
     /// Inserts an `element` at `index` within the deque, shifting all elements
     /// with indices greater than or equal to `index` towards the back.
     ///
@@ -1352,7 +2040,7 @@ impl<T> SliceDeque<T> {
     ///
     /// # Panics
     ///
-    /// Panics if `index` is greater than deque's length
+    /// Panics if `index` is greater than deque's length or on OOM.
     ///
     /// # Examples
     ///
@@ -1369,32 +2057,8 @@ impl<T> SliceDeque<T> {
     /// ```
     #[inline]
     pub fn insert(&mut self, index: usize, element: T) {
-        unsafe {
-            let len = self.len();
-            assert!(index <= len);
-
-            if intrinsics::unlikely(self.is_full()) {
-                self.reserve(1);
-                // TODO: when the deque needs to grow, reserve should
-                // copy the memory to the new storage leaving a whole
-                // at the index where the new elements are to be inserted
-                // to avoid having to copy the memory again
-            }
-
-            let p = if index > self.len() / 2 {
-                let p = self.as_mut_ptr().add(index);
-                // Shift elements towards the back
-                ptr::copy(p, p.add(1), len - index);
-                self.move_tail_unchecked(1);
-                p
-            } else {
-                // Shift elements towards the front
-                self.move_head_unchecked(-1);
-                let p = self.as_mut_ptr().add(index);
-                ptr::copy(p, p.sub(1), index);
-                p
-            };
-            ptr::write(p, element); // Overwritte
+        if let Err(e) = self.try_insert(index, element) {
+            panic!("{:?}", e.1);
         }
     }
 
@@ -1441,12 +2105,143 @@ impl<T> SliceDeque<T> {
         }
     }
 
-    /// Splits the collection into two at the given index.
+    /// Rotates the deque `n` places to the left, so that the element at
+    /// index `n` becomes the first element.
     ///
-    /// Returns a newly allocated `Self`. `self` contains elements `[0, at)`,
-    /// and the returned `Self` contains elements `[at, len)`.
+    /// This is `O(1)` if the deque is full, since the buffer is mirrored and
+    /// only the head of the deque needs to move, unless the size of `T` does
+    /// not divide the size of the buffer. Otherwise, if there is
+    /// enough spare capacity, the shorter of the first `n` or the last
+    /// `len() - n` elements are moved to the other end of the deque.
     ///
-    /// Note that the capacity of `self` does not change.
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3, 4, 5];
+    /// deq.rotate_left(2);
+    /// assert_eq!(deq, [3, 4, 5, 1, 2]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn rotate_left(&mut self, n: usize) {
+        let len = self.len();
+        assert!(n <= len);
+        let m = len - n;
+        let spare = self.capacity() - len;
+        unsafe {
+            if self.is_full() && self.fills_buffer() {
+                self.move_head_unchecked(n as isize);
+                self.move_tail_unchecked(n as isize);
+            } else if n <= m && n <= spare {
+                // Move the first `n` elements after the back
+                let p = self.as_mut_ptr();
+                ptr::copy_nonoverlapping(p, p.add(len), n);
+                self.move_tail_unchecked(n as isize);
+                self.move_head_unchecked(n as isize);
+            } else if m < n && m <= spare {
+                // Move the last `m` elements before the front
+                let p = self.as_mut_ptr();
+                ptr::copy_nonoverlapping(p.add(n), self.before_head(m), m);
+                self.move_head_unchecked(-(m as isize));
+                self.move_tail_unchecked(-(m as isize));
+            } else {
+                self.as_mut_slice().rotate_left(n);
+            }
+        }
+    }
+
+    /// Rotates the deque `n` places to the right, so that the element at
+    /// index `len() - n` becomes the first element.
+    ///
+    /// See `rotate_left`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3, 4, 5];
+    /// deq.rotate_right(2);
+    /// assert_eq!(deq, [4, 5, 1, 2, 3]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn rotate_right(&mut self, n: usize) {
+        let len = self.len();
+        assert!(n <= len);
+        self.rotate_left(len - n);
+    }
+
+    /// Returns the deque rotated `n` places to the left as a slice, without
+    /// moving any elements.
+    ///
+    /// Since the buffer is mirrored, any rotation of a full deque is
+    /// contiguous in memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the deque, or if `n` is
+    /// neither `0` nor `len()` and the deque is not full or the size of `T`
+    /// does not divide the size of the buffer, which can only happen if the
+    /// size of `T` is not a power of two.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// let mut deq = SliceDeque::with_capacity(1);
+    /// let cap = deq.capacity();
+    /// deq.extend(0..cap);
+    /// let view = deq.rotated_view(2);
+    /// assert_eq!(view.len(), cap);
+    /// assert_eq!(view[0], 2);
+    /// assert_eq!(view[cap - 1], 1);
+    /// ```
+    #[inline]
+    pub fn rotated_view(&self, n: usize) -> &[T] {
+        let len = self.len();
+        assert!(n <= len);
+        if n == 0 || n == len {
+            return self.as_slice();
+        }
+        assert!(self.is_full(), "rotated view of a deque that is not full");
+        assert!(
+            self.fills_buffer(),
+            "rotated view of a deque whose elements do not fill its buffer"
+        );
+        unsafe {
+            // The view starts at the `n`-th element after the head, wrapped
+            // into the first half of the buffer:
+            let cap = self.capacity();
+            let head = (self.as_ptr() as usize - self.buf.ptr() as usize)
+                / mem::size_of::<T>();
+            slice::from_raw_parts(self.buf.ptr().add((head + n) % cap), len)
+        }
+    }
+
+    /// Do the elements of a full deque fill its buffer exactly?
+    ///
+    /// Only then is the slot after the back of a full deque the mirror of its
+    /// front.
+    #[inline]
+    fn fills_buffer(&self) -> bool {
+        let region_size = Buffer::<T>::size_in_bytes(self.buf.len()) / 2;
+        self.capacity() * mem::size_of::<T>() == region_size
+    }
+
+    /// Attempts to split the collection into two at the given index.
+    ///
+    /// See `split_off`. On failure `self` is left unchanged.
     ///
     /// # Panics
     ///
@@ -1459,17 +2254,17 @@ impl<T> SliceDeque<T> {
     /// # use slice_deque::SliceDeque;
     /// # fn main() {
     /// let mut deq = sdeq![1, 2, 3];
-    /// let deq2 = deq.split_off(1);
+    /// let deq2 = deq.try_split_off(1).unwrap();
     /// assert_eq!(deq, [1]);
     /// assert_eq!(deq2, [2, 3]);
     /// # }
     /// ```
     #[inline]
-    pub fn split_off(&mut self, at: usize) -> Self {
+    pub fn try_split_off(&mut self, at: usize) -> Result<Self, AllocError> {
         assert!(at <= self.len(), "`at` out of bounds");
 
         let other_len = self.len() - at;
-        let mut other = Self::with_capacity(other_len);
+        let mut other = Self::try_with_capacity(other_len)?;
 
         unsafe {
             self.move_tail_unchecked(-(other_len as isize));
@@ -1481,38 +2276,130 @@ impl<T> SliceDeque<T> {
                 other.len(),
             );
         }
-        other
+        Ok(other)
     }
 
-    /// Retains only the elements specified by the predicate.
+    /// Splits the collection into two at the given index.
     ///
-    /// That is, remove all elements `e` such that `f(&e)` returns `false`.
-    /// This method operates in place and preserves the order of the
-    /// retained elements.
+    /// Returns a newly allocated `Self`. `self` contains elements `[0, at)`,
+    /// and the returned `Self` contains elements `[at, len)`.
+    ///
+    /// Note that the capacity of `self` does not change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len` or on OOM.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```rust
     /// # #[macro_use] extern crate slice_deque;
     /// # use slice_deque::SliceDeque;
     /// # fn main() {
-    /// let mut deq = sdeq![1, 2, 3, 4];
-    /// deq.retain(|&x| x % 2 == 0);
-    /// assert_eq!(deq, [2, 4]);
+    /// let mut deq = sdeq![1, 2, 3];
+    /// let deq2 = deq.split_off(1);
+    /// assert_eq!(deq, [1]);
+    /// assert_eq!(deq2, [2, 3]);
     /// # }
     /// ```
     #[inline]
-    pub fn retain<F>(&mut self, mut f: F)
-    where
-        F: FnMut(&T) -> bool,
-    {
-        let len = self.len();
-        let mut del = 0;
-        {
+    pub fn split_off(&mut self, at: usize) -> Self {
+        self.try_split_off(at).unwrap_or_else(|e| panic!("{:?}", e))
+    }
+
+    /// Modifies the `SliceDeque` in-place so that `len()` is equal to
+    /// `new_len`, either by removing excess elements or by appending values
+    /// returned by calling `f` to the back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2];
+    /// let mut v = 2;
+    /// deq.resize_with(4, || {
+    ///     v += 1;
+    ///     v
+    /// });
+    /// assert_eq!(deq, [1, 2, 3, 4]);
+    ///
+    /// deq.resize_with(1, || unreachable!());
+    /// assert_eq!(deq, [1]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn resize_with<F>(&mut self, new_len: usize, f: F)
+    where
+        F: FnMut() -> T,
+    {
+        let len = self.len();
+        if new_len > len {
+            self.extend_with(new_len - len, ExtendFunc(f));
+        } else {
+            self.truncate(new_len);
+        }
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// That is, remove all elements `e` such that `f(&e)` returns `false`.
+    /// This method operates in place and preserves the order of the
+    /// retained elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3, 4];
+    /// deq.retain(|&x| x % 2 == 0);
+    /// assert_eq!(deq, [2, 4]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|x| f(x))
+    }
+
+    /// Retains only the elements specified by the predicate, passing a
+    /// mutable reference to each element.
+    ///
+    /// That is, remove all elements `e` such that `f(&mut e)` returns
+    /// `false`. This method operates in place and preserves the order of the
+    /// retained elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3, 4];
+    /// deq.retain_mut(|x| {
+    ///     *x += 1;
+    ///     *x % 2 == 0
+    /// });
+    /// assert_eq!(deq, [2, 4]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let len = self.len();
+        let mut del = 0;
+        {
             let v = &mut **self;
 
             for i in 0..len {
-                if !f(&v[i]) {
+                if !f(&mut v[i]) {
                     del += 1;
                 } else if del > 0 {
                     v.swap(i - del, i);
@@ -1666,31 +2553,55 @@ impl<T> SliceDeque<T> {
 
     /// Extend the `SliceDeque` by `n` values, using the given generator.
     #[inline]
-    fn extend_with<E: ExtendWith<T>>(&mut self, n: usize, value: E) {
+    fn extend_with<E: ExtendWith<T>>(&mut self, n: usize, mut value: E) {
         self.reserve(n);
 
         unsafe {
-            let mut ptr = self.as_mut_ptr().add(self.len());
+            let ptr = self.as_mut_ptr();
+            // Use a local length, which is set by the scope guard in case
+            // next() panics
+            let mut local_len = SetLenOnDrop::new(self);
 
             // Write all elements except the last one
             for _ in 1..n {
-                ptr::write(ptr, value.next());
-                ptr = ptr.add(1);
-                // Increment the length in every step in case next() panics
-                self.move_tail_unchecked(1);
+                ptr::write(ptr.add(local_len.len), value.next());
+                local_len.len += 1;
             }
 
             if n > 0 {
                 // We can write the last element directly without cloning
                 // needlessly
-                ptr::write(ptr, value.last());
-                self.move_tail_unchecked(1);
+                ptr::write(ptr.add(local_len.len), value.last());
+                local_len.len += 1;
             }
 
             // len set by scope guard
         }
     }
 
+    /// Extend for an iterator whose `size_hint` might be exact.
+    ///
+    /// The elements covered by the lower bound of the size hint are written
+    /// without checking the capacity for each of them, which compiles down
+    /// to a `memcpy` for slice iterators over `Copy` types. The remaining
+    /// elements, if any, are pushed one by one.
+    #[inline]
+    fn extend_sized<I: Iterator<Item = T>>(&mut self, mut iterator: I) {
+        let (lower, _upper) = iterator.size_hint();
+        if lower > 0 {
+            self.reserve(lower);
+            unsafe {
+                let ptr = self.as_mut_ptr();
+                let mut local_len = SetLenOnDrop::new(self);
+                for element in iterator.by_ref().take(lower) {
+                    ptr::write(ptr.add(local_len.len), element);
+                    local_len.len += 1;
+                }
+            }
+        }
+        self.extend_desugared(iterator)
+    }
+
     /// Extend for a general iterator.
     ///
     /// This function should be the moral equivalent of:
@@ -1717,7 +2628,7 @@ impl<T> SliceDeque<T> {
             }
             debug_assert!(self.len() < self.capacity());
             unsafe {
-                ptr::write(self.get_unchecked_mut(len), element);
+                ptr::write(self.as_mut_ptr().add(len), element);
                 // NB can't overflow since we would have had to alloc the
                 // address space
                 self.move_tail_unchecked(1);
@@ -1746,6 +2657,8 @@ impl<T> SliceDeque<T> {
     /// * or the lower bound of its `size_hint()` is exact.
     ///
     /// Otherwise, a temporary deque is allocated and the tail is moved twice.
+    /// Whenever elements have to be moved, only the shorter of the head
+    /// (elements before `range`) and the tail is moved.
     ///
     /// # Panics
     ///
@@ -1812,18 +2725,22 @@ impl<T> SliceDeque<T> {
     ///
     /// But `drain_filter` is easier to use. `drain_filter` is also more
     /// efficient, because it can backshift the elements of the deque in
-    /// bulk.
+    /// bulk, and the elements after the last removed one are not moved if
+    /// it is cheaper to move the elements before them.
     ///
     /// Note that `drain_filter` also lets you mutate every element in the
     /// filter closure, regardless of whether you choose to keep or remove
     /// it.
     ///
+    /// If the returned `DrainFilter` is dropped before being exhausted, the
+    /// remaining elements are still tested and removed.
     ///
     /// # Examples
     ///
     /// Splitting a deque into evens and odds, reusing the original allocation:
     ///
     /// ```
+    /// # #![allow(deprecated)]
     /// # #[macro_use] extern crate slice_deque;
     /// # use slice_deque::SliceDeque;
     /// # fn main() {
@@ -1839,184 +2756,638 @@ impl<T> SliceDeque<T> {
     /// # }
     /// ```
     #[inline]
+    #[deprecated(note = "use `extract_if(.., filter)` instead")]
     pub fn drain_filter<F>(&mut self, filter: F) -> DrainFilter<T, F>
     where
         F: FnMut(&mut T) -> bool,
+    {
+        DrainFilter {
+            inner: self.extract_if(.., filter),
+        }
+    }
+
+    /// Creates an iterator which uses a closure to determine if an element
+    /// in `range` should be removed.
+    ///
+    /// If the closure returns `true`, the element is removed from the deque
+    /// and yielded. If the closure returns `false`, or panics, the element
+    /// remains in the deque and will not be yielded. The closure can mutate
+    /// every element, regardless of whether it is kept or removed.
+    ///
+    /// If the returned `ExtractIf` is not exhausted, e.g. because it is
+    /// dropped without iterating or the iteration short-circuits, then the
+    /// remaining elements are retained.
+    ///
+    /// The elements of the deque are backshifted in bulk, and the hole left
+    /// by the removed elements is closed by moving either the elements
+    /// before or after it, whichever are fewer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the deque.
+    ///
+    /// # Examples
+    ///
+    /// Splitting a deque into evens and odds, reusing the original allocation:
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut numbers = sdeq![1, 2, 3, 4, 5, 6, 8, 9, 11, 13, 14, 15];
+    ///
+    /// let evens = numbers
+    ///     .extract_if(.., |x| *x % 2 == 0)
+    ///     .collect::<SliceDeque<_>>();
+    /// let odds = numbers;
+    ///
+    /// assert_eq!(evens, [2, 4, 6, 8, 14]);
+    /// assert_eq!(odds, [1, 3, 5, 9, 11, 13, 15]);
+    /// # }
+    /// ```
+    ///
+    /// Using the range argument to only process a part of the deque:
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut items = sdeq![0, 0, 0, 0, 0, 0, 0, 1, 2, 1, 2, 1, 2];
+    /// let ones: SliceDeque<_> = items.extract_if(7.., |x| *x == 1).collect();
+    /// assert_eq!(items, [0, 0, 0, 0, 0, 0, 0, 2, 2, 2]);
+    /// assert_eq!(ones.len(), 3);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn extract_if<F, R>(
+        &mut self, range: R, filter: F,
+    ) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+        R: ops::RangeBounds<usize>,
     {
         let old_len = self.len();
+        let (start, end) = self.range_bounds(range);
 
         // Guard against us getting leaked (leak amplification)
         unsafe {
-            self.move_tail_unchecked(-(old_len as isize));
+            self.move_tail_unchecked(-((old_len - start) as isize));
         }
 
-        DrainFilter {
+        ExtractIf {
             deq: self,
-            idx: 0,
+            idx: start,
+            end,
             del: 0,
             old_len,
+            run_start: start,
             pred: filter,
         }
     }
-}
 
-impl<T> SliceDeque<T>
-where
-    T: Clone,
-{
-    /// Clones and appends all elements in a slice to the `SliceDeque`.
-    ///
-    /// Iterates over the slice `other`, clones each element, and then appends
-    /// it to this `SliceDeque`. The `other` slice is traversed in-order.
+    /// Removes elements from the front of the deque until `f` returns `true`
+    /// for an element.
     ///
-    /// Note that this function is same as `extend` except that it is
-    /// specialized to work with slices instead. If and when Rust gets
-    /// specialization this function will likely be deprecated (but still
-    /// available).
+    /// That is, retains only the elements specified by the predicate, like
+    /// `retain`, but stops at the first element that is retained. This is
+    /// useful for time-ordered queues where only the front can be stale, and
+    /// only the removed elements are visited.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use slice_deque::SliceDeque;
-    /// let mut deq = SliceDeque::new();
-    /// deq.push_back(1);
-    /// deq.extend_from_slice(&[2, 3, 4]);
-    /// assert_eq!(deq, [1, 2, 3, 4]);
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3, 4, 1, 2];
+    /// deq.retain_front_while(|&x| x > 2);
+    /// assert_eq!(deq, [3, 4, 1, 2]);
+    /// # }
     /// ```
     #[inline]
-    pub fn extend_from_slice(&mut self, other: &[T]) {
-        #[cfg(feature = "unstable")]
-        {
-            self.spec_extend(other.iter())
-        }
-        #[cfg(not(feature = "unstable"))]
-        {
-            self.reserve(other.len());
-            unsafe {
-                let len = self.len();
-                self.move_tail_unchecked(other.len() as isize);
-                self.get_unchecked_mut(len..).clone_from_slice(other);
-            }
-        }
+    pub fn retain_front_while<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let stale = self.iter().take_while(|x| !f(x)).count();
+        let len = self.len();
+        self.truncate_front(len - stale);
     }
 
-    /// Modifies the `SliceDeque` in-place so that `len()` is equal to
-    /// `new_len`, either by removing excess elements or by appending clones of
-    /// `value` to the back.
+    /// Removes elements from the back of the deque until `f` returns `true`
+    /// for an element.
+    ///
+    /// See `retain_front_while`.
     ///
     /// # Examples
     ///
     /// ```
     /// # #[macro_use] extern crate slice_deque;
-    /// # use slice_deque::SliceDeque;
     /// # fn main() {
-    /// let mut deq = sdeq![5, 10, 15];
-    /// assert_eq!(deq, [5, 10, 15]);
-    ///
-    /// deq.resize(2, 0);
-    /// assert_eq!(deq, [5, 10]);
-    ///
-    /// deq.resize(5, 20);
-    /// assert_eq!(deq, [5, 10, 20, 20, 20]);
+    /// let mut deq = sdeq![3, 4, 1, 2, 3, 1];
+    /// deq.retain_back_while(|&x| x > 2);
+    /// assert_eq!(deq, [3, 4, 1, 2, 3]);
     /// # }
     /// ```
     #[inline]
-    pub fn resize(&mut self, new_len: usize, value: T) {
+    pub fn retain_back_while<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let stale = self.iter().rev().take_while(|x| !f(x)).count();
         let len = self.len();
-
-        if new_len > len {
-            self.reserve(new_len - len);
-            while self.len() < new_len {
-                self.push_back(value.clone());
-            }
-        } else {
-            self.truncate(new_len);
-        }
-        debug_assert!(self.len() == new_len);
+        self.truncate_back(len - stale);
     }
-}
 
-impl<T: Default> SliceDeque<T> {
-    /// Resizes the `SliceDeque` in-place so that `len` is equal to `new_len`.
-    ///
-    /// If `new_len` is greater than `len`, the `SliceDeque` is extended by the
-    /// difference, with each additional slot filled with `Default::default()`.
-    /// If `new_len` is less than `len`, the `SliceDeque` is simply truncated.
-    ///
-    /// This method uses `Default` to create new values on every push. If
-    /// you'd rather `Clone` a given value, use [`resize`].
+    /// Provides a cursor with editing operations at the front element.
     ///
+    /// The cursor points to the "ghost" non-element if the deque is empty.
     ///
     /// # Examples
     ///
     /// ```
     /// # #[macro_use] extern crate slice_deque;
-    /// # use slice_deque::SliceDeque;
     /// # fn main() {
-    /// let mut deq = sdeq![1, 2, 3];
-    /// deq.resize_default(5);
-    /// assert_eq!(deq, [1, 2, 3, 0, 0]);
-    ///
-    /// deq.resize_default(2);
-    /// assert_eq!(deq, [1, 2]);
+    /// let mut deq = sdeq![1, 2, 4];
+    /// {
+    ///     let mut c = deq.cursor_front_mut();
+    ///     while c.current().map_or(false, |&mut x| x < 3) {
+    ///         c.move_next();
+    ///     }
+    ///     c.insert_before(3);
+    ///     assert_eq!(c.current(), Some(&mut 4));
+    /// }
+    /// assert_eq!(deq, [1, 2, 3, 4]);
     /// # }
     /// ```
-    ///
-    /// [`resize`]: #method.resize
     #[inline]
-    pub fn resize_default(&mut self, new_len: usize) {
-        let len = self.len();
-
-        if new_len > len {
-            self.extend_with(new_len - len, ExtendDefault);
-        } else {
-            self.truncate(new_len);
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            index: 0,
+            deq: self,
         }
     }
-}
 
-impl<T: PartialEq> SliceDeque<T> {
-    /// Removes consecutive repeated elements in the deque.
+    /// Provides a cursor with editing operations at the back element.
     ///
-    /// If the deque is sorted, this removes all duplicates.
+    /// The cursor points to the "ghost" non-element if the deque is empty.
     ///
     /// # Examples
     ///
     /// ```
     /// # #[macro_use] extern crate slice_deque;
-    /// # use slice_deque::SliceDeque;
     /// # fn main() {
-    /// let mut deq = sdeq![1, 2, 2, 3, 2];
+    /// let mut deq = sdeq![1, 2, 3];
+    /// {
+    ///     let mut c = deq.cursor_back_mut();
+    ///     assert_eq!(c.remove_current(), Some(3));
+    ///     c.move_prev();
+    ///     c.insert_after(4);
+    /// }
+    /// assert_eq!(deq, [1, 2, 4]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let index = self.len().saturating_sub(1);
+        CursorMut { index, deq: self }
+    }
+
+    /// Decomposes the deque into its raw components.
     ///
-    /// deq.dedup();
-    /// assert_eq!(deq, [1, 2, 3, 2]);
+    /// Returns the pointer to the beginning of the memory buffer, the
+    /// capacity of the deque, the offset in bytes of the front of the deque
+    /// from the beginning of the buffer, and the length of the deque. The
+    /// offset is in bytes because it need not be a multiple of the size of
+    /// `T` once the front has wrapped around. Because the buffer is mirrored,
+    /// the elements might wrap around the end of the first half of the
+    /// buffer, but they are always contiguous in memory.
     ///
-    /// deq.sort();
-    /// assert_eq!(deq, [1, 2, 2, 3]);
+    /// The memory can be released by rebuilding the deque with
+    /// `from_raw_parts`.
     ///
-    /// deq.dedup();
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # use std::slice;
+    /// # fn main() {
+    /// let mut deq = sdeq![2, 3];
+    /// deq.push_front(1);
+    /// let (ptr, cap, head, len) = deq.into_raw_parts();
+    /// let deq = unsafe {
+    ///     let front = (ptr as *mut u8).add(head) as *mut i32;
+    ///     let elems = slice::from_raw_parts_mut(front, len);
+    ///     SliceDeque::from_raw_parts(ptr, cap, elems)
+    /// };
     /// assert_eq!(deq, [1, 2, 3]);
     /// # }
     /// ```
     #[inline]
-    pub fn dedup(&mut self) {
-        self.dedup_by(|a, b| a == b)
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize, usize) {
+        let me = mem::ManuallyDrop::new(self);
+        unsafe {
+            let ptr = me.buf.ptr();
+            let head = if mem::size_of::<T>() == 0 {
+                0
+            } else {
+                me.as_ptr() as usize - ptr as usize
+            };
+            (ptr, me.capacity(), head, me.len())
+        }
     }
 
-    /// Removes the first instance of `item` from the deque if the item exists.
+    /// Consumes and leaks the deque, returning a mutable reference to its
+    /// elements.
+    ///
+    /// The memory of the deque, including its spare capacity, is never
+    /// released.
     ///
     /// # Examples
     ///
     /// ```
     /// # #[macro_use] extern crate slice_deque;
-    /// # use slice_deque::SliceDeque;
     /// # fn main() {
-    /// let mut deq = sdeq![1, 2, 3, 1];
-    ///
-    /// deq.remove_item(&1);
-    /// assert_eq!(deq, &[2, 3, 1]);
-    /// deq.remove_item(&1);
-    /// assert_eq!(deq, &[2, 3]);
+    /// let deq = sdeq![1, 2, 3];
+    /// let s: &'static mut [i32] = deq.leak();
+    /// s[0] = 4;
+    /// assert_eq!(s, [4, 2, 3]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn leak<'a>(self) -> &'a mut [T] {
+        let mut me = mem::ManuallyDrop::new(self);
+        unsafe { slice::from_raw_parts_mut(me.as_mut_ptr(), me.len()) }
+    }
+
+    /// Converts the deque into a boxed slice.
+    ///
+    /// The elements are moved into a new heap allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let deq = sdeq![1, 2, 3];
+    /// let b = deq.into_boxed_slice();
+    /// assert_eq!(&*b, [1, 2, 3]);
+    /// # }
+    /// ```
+    #[cfg(feature = "use_std")]
+    #[inline]
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        Vec::from(self).into_boxed_slice()
+    }
+
+    /// Attempts to create a deque from the elements of `iter`.
+    ///
+    /// On failure, the elements already taken from `iter` are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// let deq = SliceDeque::try_from_iter(0..4).unwrap();
+    /// assert_eq!(deq, [0, 1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, AllocError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iterator = iter.into_iter();
+        let (lower, _) = iterator.size_hint();
+        let mut deq = Self::try_with_capacity(lower)?;
+        for element in iterator {
+            if let Err(e) = deq.try_push_back(element) {
+                return Err(e.1);
+            }
+        }
+        Ok(deq)
+    }
+}
+
+impl<T> SliceDeque<T>
+where
+    T: Clone,
+{
+    /// Attempts to clone the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let deq = sdeq![1, 2, 3];
+    /// let deq2 = deq.try_clone().unwrap();
+    /// assert_eq!(deq, deq2);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, AllocError> {
+        let mut new = Self::try_with_capacity(self.len())?;
+        new.policy = self.policy;
+        for i in self.iter() {
+            new.push_back(i.clone());
+        }
+        Ok(new)
+    }
+
+    /// Attempts to clone and append all elements in a slice to the
+    /// `SliceDeque`.
+    ///
+    /// On failure the deque is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// let mut deq = SliceDeque::new();
+    /// deq.push_back(1);
+    /// deq.try_extend_from_slice(&[2, 3, 4]).unwrap();
+    /// assert_eq!(deq, [1, 2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn try_extend_from_slice(
+        &mut self, other: &[T],
+    ) -> Result<(), AllocError> {
+        self.try_reserve(other.len())?;
+        self.extend_from_slice(other);
+        Ok(())
+    }
+
+    /// Clones and appends all elements in a slice to the `SliceDeque`.
+    ///
+    /// Iterates over the slice `other`, clones each element, and then appends
+    /// it to this `SliceDeque`. The `other` slice is traversed in-order.
+    ///
+    /// Note that this function is same as `extend` except that it is
+    /// specialized to work with slices instead. If and when Rust gets
+    /// specialization this function will likely be deprecated (but still
+    /// available).
+    ///
+    /// Slices of primitive types like integers are copied with a single
+    /// `memcpy`. Slices of other `Copy` types are copied that way by `extend`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// let mut deq = SliceDeque::new();
+    /// deq.push_back(1);
+    /// deq.extend_from_slice(&[2, 3, 4]);
+    /// assert_eq!(deq, [1, 2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.spec_extend(other.iter())
+    }
+
+    /// Clones the elements in `src` and appends them to the back of the
+    /// `SliceDeque`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut deq = sdeq![0, 1, 2, 3];
+    /// deq.extend_from_within(1..3);
+    /// assert_eq!(deq, [0, 1, 2, 3, 1, 2]);
+    /// deq.extend_from_within(4..);
+    /// assert_eq!(deq, [0, 1, 2, 3, 1, 2, 1, 2]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn extend_from_within<R>(&mut self, src: R)
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let (start, end) = self.range_bounds(src);
+        self.reserve(end - start);
+        for i in start..end {
+            let v = self[i].clone();
+            self.push_back(v);
+        }
+    }
+
+    /// Clones and prepends all elements in a slice to the `SliceDeque`,
+    /// preserving their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// let mut deq = SliceDeque::new();
+    /// deq.push_back(4);
+    /// deq.extend_front_from_slice(&[1, 2, 3]);
+    /// assert_eq!(deq, [1, 2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn extend_front_from_slice(&mut self, other: &[T]) {
+        self.extend_front(other.iter().cloned())
+    }
+
+    /// Clones and inserts all elements in a slice at `index` within the
+    /// deque, shifting all elements with indices greater than or equal to
+    /// `index` towards the back.
+    ///
+    /// If the deque needs to grow, the elements are moved to the new storage
+    /// around the inserted range, so that they are only copied once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than deque's length or on OOM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 5];
+    /// deq.insert_slice(2, &[3, 4]);
+    /// assert_eq!(deq, &[1, 2, 3, 4, 5]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn insert_slice(&mut self, index: usize, other: &[T]) {
+        let len = self.len();
+        assert!(index <= len);
+        unsafe {
+            if let Err(e) = self.open_gap(index, other.len()) {
+                panic!("{:?}", e);
+            }
+            // Hide the gap and the tail in case `clone` panics:
+            let tail_len = len - index;
+            self.move_tail_unchecked(-((other.len() + tail_len) as isize));
+            for v in other {
+                let p = self.as_mut_ptr().add(self.len());
+                ptr::write(p, v.clone());
+                self.move_tail_unchecked(1);
+            }
+            self.move_tail_unchecked(tail_len as isize);
+        }
+    }
+
+    /// Attempts to modify the `SliceDeque` in-place so that `len()` is equal
+    /// to `new_len`, either by removing excess elements or by appending clones
+    /// of `value` to the back.
+    ///
+    /// On failure the deque is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![5, 10, 15];
+    /// deq.try_resize(5, 20).unwrap();
+    /// assert_eq!(deq, [5, 10, 15, 20, 20]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn try_resize(
+        &mut self, new_len: usize, value: T,
+    ) -> Result<(), AllocError> {
+        let len = self.len();
+        if new_len > len {
+            self.try_reserve(new_len - len)?;
+        }
+        self.resize(new_len, value);
+        Ok(())
+    }
+
+    /// Modifies the `SliceDeque` in-place so that `len()` is equal to
+    /// `new_len`, either by removing excess elements or by appending clones of
+    /// `value` to the back.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![5, 10, 15];
+    /// assert_eq!(deq, [5, 10, 15]);
+    ///
+    /// deq.resize(2, 0);
+    /// assert_eq!(deq, [5, 10]);
+    ///
+    /// deq.resize(5, 20);
+    /// assert_eq!(deq, [5, 10, 20, 20, 20]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        let len = self.len();
+
+        if new_len > len {
+            self.reserve(new_len - len);
+            while self.len() < new_len {
+                self.push_back(value.clone());
+            }
+        } else {
+            self.truncate(new_len);
+        }
+        debug_assert!(self.len() == new_len);
+    }
+}
+
+impl<T: Default> SliceDeque<T> {
+    /// Resizes the `SliceDeque` in-place so that `len` is equal to `new_len`.
+    ///
+    /// If `new_len` is greater than `len`, the `SliceDeque` is extended by the
+    /// difference, with each additional slot filled with `Default::default()`.
+    /// If `new_len` is less than `len`, the `SliceDeque` is simply truncated.
+    ///
+    /// This method uses `Default` to create new values on every push. If
+    /// you'd rather `Clone` a given value, use [`resize`].
+    ///
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3];
+    /// deq.resize_default(5);
+    /// assert_eq!(deq, [1, 2, 3, 0, 0]);
+    ///
+    /// deq.resize_default(2);
+    /// assert_eq!(deq, [1, 2]);
+    /// # }
+    /// ```
+    ///
+    /// [`resize`]: #method.resize
+    #[inline]
+    pub fn resize_default(&mut self, new_len: usize) {
+        let len = self.len();
+
+        if new_len > len {
+            self.extend_with(new_len - len, ExtendDefault);
+        } else {
+            self.truncate(new_len);
+        }
+    }
+}
+
+impl<T: PartialEq> SliceDeque<T> {
+    /// Removes consecutive repeated elements in the deque.
+    ///
+    /// If the deque is sorted, this removes all duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 2, 3, 2];
+    ///
+    /// deq.dedup();
+    /// assert_eq!(deq, [1, 2, 3, 2]);
+    ///
+    /// deq.sort();
+    /// assert_eq!(deq, [1, 2, 2, 3]);
+    ///
+    /// deq.dedup();
+    /// assert_eq!(deq, [1, 2, 3]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Removes the first instance of `item` from the deque if the item exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::SliceDeque;
+    /// # fn main() {
+    /// let mut deq = sdeq![1, 2, 3, 1];
+    ///
+    /// deq.remove_item(&1);
+    /// assert_eq!(deq, &[2, 3, 1]);
+    /// deq.remove_item(&1);
+    /// assert_eq!(deq, &[2, 3]);
     /// # }
     /// ```
     #[inline]
@@ -2086,11 +3457,7 @@ impl<T> Default for SliceDeque<T> {
 impl<T: Clone> Clone for SliceDeque<T> {
     #[inline]
     fn clone(&self) -> Self {
-        let mut new = Self::with_capacity(self.len());
-        for i in self.iter() {
-            new.push_back(i.clone());
-        }
-        new
+        self.try_clone().unwrap_or_else(|e| panic!("{:?}", e))
     }
     #[inline]
     fn clone_from(&mut self, other: &Self) {
@@ -2123,6 +3490,129 @@ impl<'a, T: Clone> From<&'a mut [T]> for SliceDeque<T> {
     }
 }
 
+impl<T, const N: usize> From<[T; N]> for SliceDeque<T> {
+    /// Moves the elements of the array into a new deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// let deq = SliceDeque::from([1, 2, 3]);
+    /// assert_eq!(deq, [1, 2, 3]);
+    /// ```
+    #[inline]
+    fn from(a: [T; N]) -> Self {
+        let a = mem::ManuallyDrop::new(a);
+        unsafe { Self::steal_from_slice(&a[..]) }
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<T> From<Vec<T>> for SliceDeque<T> {
+    /// Moves the elements of the vector into a new deque.
+    ///
+    /// The elements are copied into the deque bitwise, without cloning them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// let deq = SliceDeque::from(vec![String::from("a"), String::from("b")]);
+    /// assert_eq!(deq, ["a", "b"]);
+    /// ```
+    #[inline]
+    fn from(mut v: Vec<T>) -> Self {
+        unsafe {
+            let deq = Self::steal_from_slice(&v);
+            v.set_len(0);
+            deq
+        }
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<T> From<Box<[T]>> for SliceDeque<T> {
+    /// Moves the elements of the boxed slice into a new deque.
+    #[inline]
+    fn from(b: Box<[T]>) -> Self {
+        Self::from(b.into_vec())
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<T> From<std::collections::VecDeque<T>> for SliceDeque<T> {
+    /// Moves the elements of the `VecDeque` into a new deque.
+    ///
+    /// The `VecDeque` is first made contiguous in place, and its elements are
+    /// then copied into the deque bitwise, without cloning them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slice_deque::SliceDeque;
+    /// # use std::collections::VecDeque;
+    /// let mut v = VecDeque::new();
+    /// v.push_back(2);
+    /// v.push_front(1);
+    /// let deq = SliceDeque::from(v);
+    /// assert_eq!(deq, [1, 2]);
+    /// ```
+    #[inline]
+    fn from(v: std::collections::VecDeque<T>) -> Self {
+        Self::from(Vec::from(v))
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<T> From<SliceDeque<T>> for Vec<T> {
+    /// Moves the elements of the deque into a new vector.
+    ///
+    /// The elements are copied into the vector bitwise, without cloning them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let deq = sdeq![1, 2, 3];
+    /// let v: Vec<_> = deq.into();
+    /// assert_eq!(v, [1, 2, 3]);
+    /// # }
+    /// ```
+    #[inline]
+    fn from(mut deq: SliceDeque<T>) -> Self {
+        unsafe {
+            let len = deq.len();
+            let mut v = Self::with_capacity(len);
+            ptr::copy_nonoverlapping(deq.as_ptr(), v.as_mut_ptr(), len);
+            v.set_len(len);
+            deq.move_tail_unchecked(-(len as isize));
+            v
+        }
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<T> From<SliceDeque<T>> for std::collections::VecDeque<T> {
+    /// Moves the elements of the deque into a new `VecDeque`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use std::collections::VecDeque;
+    /// # fn main() {
+    /// let deq = sdeq![1, 2, 3];
+    /// let v: VecDeque<_> = deq.into();
+    /// assert_eq!(v, [1, 2, 3]);
+    /// # }
+    /// ```
+    #[inline]
+    fn from(deq: SliceDeque<T>) -> Self {
+        Self::from(Vec::from(deq))
+    }
+}
+
 impl<T: hash::Hash> hash::Hash for SliceDeque<T> {
     #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
@@ -2134,11 +3624,8 @@ impl<T: hash::Hash> hash::Hash for SliceDeque<T> {
 // PartialEq implementations:
 
 macro_rules! __impl_slice_eq1 {
-    ($Lhs:ty, $Rhs:ty) => {
-        __impl_slice_eq1! { $Lhs, $Rhs, Sized }
-    };
-    ($Lhs:ty, $Rhs:ty, $Bound:ident) => {
-        impl<'a, 'b, A: $Bound, B> PartialEq<$Rhs> for $Lhs
+    ([$($vars:tt)*] $Lhs:ty, $Rhs:ty) => {
+        impl<'b, A, B, $($vars)*> PartialEq<$Rhs> for $Lhs
         where
             A: PartialEq<B>,
         {
@@ -2150,30 +3637,32 @@ macro_rules! __impl_slice_eq1 {
     };
 }
 
-__impl_slice_eq1! { SliceDeque<A>, SliceDeque<B> }
-__impl_slice_eq1! { SliceDeque<A>, &'b [B] }
-__impl_slice_eq1! { SliceDeque<A>, &'b mut [B] }
+__impl_slice_eq1! { [] SliceDeque<A>, SliceDeque<B> }
+__impl_slice_eq1! { [] SliceDeque<A>, [B] }
+__impl_slice_eq1! { [] SliceDeque<A>, &'b [B] }
+__impl_slice_eq1! { [] SliceDeque<A>, &'b mut [B] }
+__impl_slice_eq1! { [const N: usize] SliceDeque<A>, [B; N] }
+__impl_slice_eq1! { [const N: usize] SliceDeque<A>, &'b [B; N] }
 
 #[cfg(feature = "use_std")]
-__impl_slice_eq1! { SliceDeque<A>, Vec<B> }
+__impl_slice_eq1! { [] SliceDeque<A>, Vec<B> }
+#[cfg(feature = "use_std")]
+__impl_slice_eq1! { [] SliceDeque<A>, Box<[B]> }
 
-macro_rules! array_impls {
-    ($($N: expr)+) => {
-        $(
-            // NOTE: some less important impls are omitted to reduce code bloat
-            __impl_slice_eq1! { SliceDeque<A>, [B; $N] }
-            __impl_slice_eq1! { SliceDeque<A>, &'b [B; $N] }
-        )+
+#[cfg(feature = "use_std")]
+impl<A, B> PartialEq<std::collections::VecDeque<B>> for SliceDeque<A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &std::collections::VecDeque<B>) -> bool {
+        let (front, back) = other.as_slices();
+        self.len() == other.len()
+            && self[..front.len()] == *front
+            && self[front.len()..] == *back
     }
 }
 
-array_impls! {
-    0  1  2  3  4  5  6  7  8  9
-        10 11 12 13 14 15 16 17 18 19
-        20 21 22 23 24 25 26 27 28 29
-        30 31 32
-}
-
 ///////////////////////////////////////////////////////////////////////////////
 
 impl<T: Eq> Eq for SliceDeque<T> {}
@@ -2185,6 +3674,13 @@ impl<T: PartialOrd> PartialOrd for SliceDeque<T> {
     }
 }
 
+impl<T: PartialOrd> PartialOrd<[T]> for SliceDeque<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &[T]) -> Option<cmp::Ordering> {
+        PartialOrd::partial_cmp(&**self, other)
+    }
+}
+
 impl<'a, T: PartialOrd> PartialOrd<&'a [T]> for SliceDeque<T> {
     #[inline]
     fn partial_cmp(&self, other: &&'a [T]) -> Option<cmp::Ordering> {
@@ -2192,6 +3688,25 @@ impl<'a, T: PartialOrd> PartialOrd<&'a [T]> for SliceDeque<T> {
     }
 }
 
+#[cfg(feature = "use_std")]
+impl<T: PartialOrd> PartialOrd<std::collections::VecDeque<T>>
+    for SliceDeque<T>
+{
+    #[inline]
+    fn partial_cmp(
+        &self, other: &std::collections::VecDeque<T>,
+    ) -> Option<cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for SliceDeque<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
 /// A draining iterator for `SliceDeque<T>`.
 ///
 /// This `struct` is created by the [`drain`] method on [`SliceDeque`].
@@ -2215,6 +3730,27 @@ impl<'a, T: 'a + fmt::Debug> fmt::Debug for Drain<'a, T> {
     }
 }
 
+impl<'a, T: 'a> Drain<'a, T> {
+    /// Returns the remaining elements of this iterator as a slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate slice_deque;
+    /// # fn main() {
+    /// let mut deq = sdeq!['a', 'b', 'c'];
+    /// let mut drain = deq.drain(..);
+    /// assert_eq!(drain.as_slice(), &['a', 'b', 'c']);
+    /// let _ = drain.next().unwrap();
+    /// assert_eq!(drain.as_slice(), &['b', 'c']);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+}
+
 unsafe impl<'a, T: Sync> Sync for Drain<'a, T> {}
 unsafe impl<'a, T: Send> Send for Drain<'a, T> {}
 
@@ -2248,30 +3784,42 @@ impl<'a, T> Drop for Drain<'a, T> {
         // exhaust self first
         self.for_each(|_| {});
 
-        if self.tail_len > 0 {
-            unsafe {
-                let source_deq = self.deq.as_mut();
-                // memmove back untouched tail, update to new length
+        unsafe {
+            let source_deq = self.deq.as_mut();
+            if self.tail_len > 0 {
                 let start = source_deq.len();
-                let tail = self.tail_start;
-                let src = source_deq.as_ptr().add(tail);
-                let dst = source_deq.as_mut_ptr().add(start);
-                ptr::copy(src, dst, self.tail_len);
-                source_deq.move_tail_unchecked(self.tail_len as isize);
+                let gap = self.tail_start - start;
+                if start < self.tail_len {
+                    // memmove forward the untouched head, and move the head
+                    // of the deque past the hole
+                    let src = source_deq.as_ptr();
+                    let dst = source_deq.as_mut_ptr().add(gap);
+                    ptr::copy(src, dst, start);
+                    source_deq
+                        .move_tail_unchecked((gap + self.tail_len) as isize);
+                    source_deq.move_head_unchecked(gap as isize);
+                } else {
+                    // memmove back untouched tail, update to new length
+                    let tail = self.tail_start;
+                    let src = source_deq.as_ptr().add(tail);
+                    let dst = source_deq.as_mut_ptr().add(start);
+                    ptr::copy(src, dst, self.tail_len);
+                    source_deq.move_tail_unchecked(self.tail_len as isize);
+                }
             }
+            source_deq.auto_shrink();
         }
     }
 }
 
-#[cfg(feature = "unstable")]
 impl<'a, T> ExactSizeIterator for Drain<'a, T> {
+    #[cfg(feature = "unstable")]
     #[inline]
     fn is_empty(&self) -> bool {
         self.iter.is_empty()
     }
 }
 
-#[cfg(feature = "unstable")]
 impl<'a, T> iter::FusedIterator for Drain<'a, T> {}
 
 /// An iterator that moves out of a deque.
@@ -2300,8 +3848,6 @@ impl<T: fmt::Debug> fmt::Debug for IntoIter<T> {
 
 impl<T> IntoIter<T> {
     /// Returns the element slice
-    #[cfg(feature = "unstable")]
-    #[allow(clippy::option_unwrap_used)]
     #[inline]
     fn elems(&mut self) -> &mut [T] {
         unsafe {
@@ -2425,15 +3971,14 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
-#[cfg(feature = "unstable")]
 impl<T> ExactSizeIterator for IntoIter<T> {
+    #[cfg(feature = "unstable")]
     #[inline]
     fn is_empty(&self) -> bool {
         self.ptr == self.end
     }
 }
 
-#[cfg(feature = "unstable")]
 impl<T> iter::FusedIterator for IntoIter<T> {}
 
 #[cfg(feature = "unstable")]
@@ -2563,6 +4108,73 @@ trait SpecExtend<T, I> {
     fn spec_extend(&mut self, iter: I);
 }
 
+/// Returns `true` if `T` and `U` are the same type, ignoring lifetimes.
+///
+/// Without the `unstable` feature, this selects the fast paths that would
+/// otherwise need specialization. It is folded to a constant once the types
+/// are known.
+#[inline(always)]
+fn same_type<T: ?Sized, U: ?Sized>() -> bool {
+    type_id::<T>() == type_id::<U>()
+}
+
+/// Returns the `TypeId` of `T`, which does not need to be `'static`.
+///
+/// Type ids do not depend on lifetimes, so the trait object used to query it
+/// can have its lifetime extended to `'static`.
+#[inline(always)]
+fn type_id<T: ?Sized>() -> any::TypeId {
+    /// Object safe trait that queries the `TypeId` of a type parameter.
+    trait NonStaticAny {
+        /// Returns the `TypeId` of the type parameter.
+        fn get_type_id(&self) -> any::TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for marker::PhantomData<T> {
+        fn get_type_id(&self) -> any::TypeId
+        where
+            Self: 'static,
+        {
+            any::TypeId::of::<T>()
+        }
+    }
+
+    let phantom = marker::PhantomData::<T>;
+    let any: &dyn NonStaticAny = &phantom;
+    unsafe {
+        mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(any)
+    }
+    .get_type_id()
+}
+
+/// Moves `x` into a value of type `U`.
+///
+/// # Safety
+///
+/// `T` and `U` must be the same type up to lifetimes, and the lifetimes of
+/// `U` must not outlive those of `T`.
+#[inline(always)]
+unsafe fn cast_same<T, U>(x: T) -> U {
+    debug_assert!(same_type::<T, U>());
+    let x = mem::ManuallyDrop::new(x);
+    ptr::read(&*x as *const T as *const U)
+}
+
+/// Returns `true` if `T` is a primitive integer, floating-point, `bool` or
+/// `char` type, which are `Copy` and cloned bit by bit.
+#[inline(always)]
+fn is_primitive<T: ?Sized>() -> bool {
+    macro_rules! any_of {
+        ($($t:ty),*) => (false $(|| same_type::<T, $t>())*)
+    }
+    any_of!(
+        u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32,
+        f64, bool, char
+    )
+}
+
 /// Default implementation of `SpecExtend::from_iter`.
 #[inline(always)]
 fn from_iter_default<T, I: Iterator<Item = T>>(
@@ -2580,7 +4192,7 @@ fn from_iter_default<T, I: Iterator<Item = T>>(
             let mut deque =
                 SliceDeque::<T>::with_capacity(lower.saturating_add(1));
             unsafe {
-                ptr::write(deque.get_unchecked_mut(0), element);
+                ptr::write(deque.as_mut_ptr(), element);
                 deque.move_tail_unchecked(1);
             }
             deque
@@ -2601,17 +4213,25 @@ where
 
     #[cfg(feature = "unstable")]
     default fn spec_extend(&mut self, iter: I) {
-        self.extend_desugared(iter)
+        self.extend_sized(iter)
     }
 
     #[cfg(not(feature = "unstable"))]
     fn from_iter(iterator: I) -> Self {
-        from_iter_default(iterator)
+        if same_type::<I, IntoIter<T>>() {
+            unsafe { Self::from_into_iter(cast_same(iterator)) }
+        } else {
+            from_iter_default(iterator)
+        }
     }
 
     #[cfg(not(feature = "unstable"))]
     fn spec_extend(&mut self, iter: I) {
-        self.extend_desugared(iter)
+        if same_type::<I, IntoIter<T>>() {
+            unsafe { self.extend_from_into_iter(cast_same(iter)) }
+        } else {
+            self.extend_sized(iter)
+        }
     }
 }
 
@@ -2658,33 +4278,12 @@ where
 
 #[cfg(feature = "unstable")]
 impl<T> SpecExtend<T, IntoIter<T>> for SliceDeque<T> {
-    fn from_iter(mut iterator: IntoIter<T>) -> Self {
-        // A common case is passing a deque into a function which immediately
-        // re-collects into a deque. We can short circuit this if the IntoIter
-        // has not been advanced at all.
-        if iterator.buf.as_ptr() as *const _ == iterator.ptr {
-            unsafe {
-                let deq = Self::from_raw_parts(
-                    iterator.buf.as_ptr(),
-                    iterator.cap,
-                    iterator.elems(),
-                );
-                #[allow(clippy::mem_forget)]
-                mem::forget(iterator);
-                deq
-            }
-        } else {
-            let mut deque = Self::new();
-            deque.spec_extend(iterator);
-            deque
-        }
+    fn from_iter(iterator: IntoIter<T>) -> Self {
+        Self::from_into_iter(iterator)
     }
 
-    fn spec_extend(&mut self, mut iterator: IntoIter<T>) {
-        unsafe {
-            self.append_elements(iterator.as_slice() as _);
-        }
-        iterator.ptr = iterator.end;
+    fn spec_extend(&mut self, iterator: IntoIter<T>) {
+        self.extend_from_into_iter(iterator)
     }
 }
 
@@ -2699,7 +4298,12 @@ where
     }
 
     fn spec_extend(&mut self, iterator: I) {
-        self.spec_extend(iterator.cloned())
+        if is_primitive::<T>() && same_type::<I, slice::Iter<'a, T>>() {
+            let iterator: slice::Iter<'a, T> = unsafe { cast_same(iterator) };
+            unsafe { self.append_elements(iterator.as_slice()) }
+        } else {
+            self.spec_extend(iterator.cloned())
+        }
     }
 }
 
@@ -2740,10 +4344,39 @@ impl<T> iter::FromIterator<T> for SliceDeque<T> {
     }
 }
 
+/// Sets the length of a deque when dropped, so that loops writing elements
+/// can use a local length, and the elements written are kept even if the
+/// loop panics.
+struct SetLenOnDrop<'a, T: 'a> {
+    /// The deque being written to.
+    deq: &'a mut SliceDeque<T>,
+    /// The length of the deque, including the elements written.
+    len: usize,
+}
+
+impl<'a, T> SetLenOnDrop<'a, T> {
+    /// Creates a guard for `deq` starting at its current length.
+    #[inline]
+    fn new(deq: &'a mut SliceDeque<T>) -> Self {
+        let len = deq.len();
+        SetLenOnDrop { deq, len }
+    }
+}
+
+impl<'a, T> Drop for SetLenOnDrop<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let written = self.len - self.deq.len();
+            self.deq.move_tail_unchecked(written as isize);
+        }
+    }
+}
+
 /// This code generalises `extend_with_{element,default}`.
 trait ExtendWith<T> {
     /// TODO: docs
-    fn next(&self) -> T;
+    fn next(&mut self) -> T;
     /// TODO: docs
     fn last(self) -> T;
 }
@@ -2751,7 +4384,7 @@ trait ExtendWith<T> {
 /// TODO: docs
 struct ExtendElement<T>(T);
 impl<T: Clone> ExtendWith<T> for ExtendElement<T> {
-    fn next(&self) -> T {
+    fn next(&mut self) -> T {
         self.0.clone()
     }
     fn last(self) -> T {
@@ -2762,7 +4395,7 @@ impl<T: Clone> ExtendWith<T> for ExtendElement<T> {
 /// TODO: docs
 struct ExtendDefault;
 impl<T: Default> ExtendWith<T> for ExtendDefault {
-    fn next(&self) -> T {
+    fn next(&mut self) -> T {
         Default::default()
     }
     fn last(self) -> T {
@@ -2770,90 +4403,77 @@ impl<T: Default> ExtendWith<T> for ExtendDefault {
     }
 }
 
-/// TODO: docs
-/// FIXME: not used, this should be used by the sdeq! macro? Remove this maybe.
-#[doc(hidden)]
-pub fn from_elem<T: Clone>(elem: T, n: usize) -> SliceDeque<T> {
-    <T as SpecFromElem>::from_elem(elem, n)
-}
-
-/// Specialization trait used for `SliceDeque::from_elem`.
-trait SpecFromElem: Sized {
-    /// TODO: docs
-    fn from_elem(elem: Self, n: usize) -> SliceDeque<Self>;
-}
-
-impl<T: Clone> SpecFromElem for T {
-    #[cfg(feature = "unstable")]
-    default fn from_elem(elem: Self, n: usize) -> SliceDeque<Self> {
-        let mut v = SliceDeque::with_capacity(n);
-        v.extend_with(n, ExtendElement(elem));
-        v
+/// Extends with the values returned by a closure.
+struct ExtendFunc<F>(F);
+impl<T, F: FnMut() -> T> ExtendWith<T> for ExtendFunc<F> {
+    fn next(&mut self) -> T {
+        (self.0)()
     }
-
-    #[cfg(not(feature = "unstable"))]
-    fn from_elem(elem: Self, n: usize) -> SliceDeque<Self> {
-        let mut v = SliceDeque::with_capacity(n);
-        v.extend_with(n, ExtendElement(elem));
-        v
+    fn last(mut self) -> T {
+        (self.0)()
     }
 }
 
-#[cfg(feature = "unstable")]
-impl SpecFromElem for u8 {
-    #[inline]
-    fn from_elem(elem: Self, n: usize) -> SliceDeque<Self> {
+/// Creates a deque with `n` clones of `elem`.
+///
+/// This is used by the `sdeq![elem; n]` macro. Integers whose bytes are all
+/// equal, like zero, are written with a single `memset`.
+#[doc(hidden)]
+pub fn from_elem<T: Clone>(elem: T, n: usize) -> SliceDeque<T> {
+    let mut v = SliceDeque::with_capacity(n);
+    if let Some(byte) = fill_byte(&elem) {
         unsafe {
-            let mut v = SliceDeque::with_capacity(n);
-            ptr::write_bytes(v.as_mut_ptr(), elem, n);
+            ptr::write_bytes(v.as_mut_ptr(), byte, n);
             v.move_tail_unchecked(n as isize);
-            v
         }
+    } else {
+        v.extend_with(n, ExtendElement(elem));
     }
+    v
 }
 
-macro_rules! impl_spec_from_elem {
-    ($t:ty, $is_zero:expr) => {
-        #[cfg(feature = "unstable")]
-        impl SpecFromElem for $t {
-            #[inline]
-            fn from_elem(elem: Self, n: usize) -> SliceDeque<Self> {
-                let mut v = SliceDeque::with_capacity(n);
-                v.extend_with(n, ExtendElement(elem));
-                v
-            }
-        }
-    };
-}
-
-impl_spec_from_elem!(i8, |x| x == 0);
-impl_spec_from_elem!(i16, |x| x == 0);
-impl_spec_from_elem!(i32, |x| x == 0);
-impl_spec_from_elem!(i64, |x| x == 0);
-#[cfg(feature = "unstable")]
-impl_spec_from_elem!(i128, |x| x == 0);
-impl_spec_from_elem!(isize, |x| x == 0);
-
-impl_spec_from_elem!(u16, |x| x == 0);
-impl_spec_from_elem!(u32, |x| x == 0);
-impl_spec_from_elem!(u64, |x| x == 0);
-#[cfg(feature = "unstable")]
-impl_spec_from_elem!(u128, |x| x == 0);
-impl_spec_from_elem!(usize, |x| x == 0);
-
-impl_spec_from_elem!(f32, |x: f32| x == 0. && x.is_sign_positive());
-impl_spec_from_elem!(f64, |x: f64| x == 0. && x.is_sign_positive());
+/// Returns the byte that `elem` consists of if `elem` is an integer and all
+/// of its bytes are equal.
+#[inline(always)]
+fn fill_byte<T>(elem: &T) -> Option<u8> {
+    macro_rules! fill_byte_of {
+        ($($t:ty),*) => {
+            $(
+                if same_type::<T, $t>() {
+                    let bytes = unsafe { *(elem as *const T as *const $t) }
+                        .to_ne_bytes();
+                    return if bytes.iter().all(|&b| b == bytes[0]) {
+                        Some(bytes[0])
+                    } else {
+                        None
+                    };
+                }
+            )*
+        };
+    }
+    fill_byte_of!(
+        u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+    );
+    None
+}
 
 /// Extend implementation that copies elements out of references before
 /// pushing them onto the `SliceDeque`.
 ///
-/// This implementation is specialized for slice iterators, where it uses
-/// [`copy_from_slice`] to append the entire slice at once.
-///
-/// [`copy_from_slice`]: ../../std/primitive.slice.html#method.copy_from_slice
+/// The elements of slice iterators are copied with a single `memcpy`, and
+/// those of other iterators with an exact size hint are copied at once.
 impl<'a, T: 'a + Copy> Extend<&'a T> for SliceDeque<T> {
     fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
-        self.spec_extend(iter.into_iter())
+        let iter = iter.into_iter();
+        #[cfg(not(feature = "unstable"))]
+        {
+            if same_type::<I::IntoIter, slice::Iter<'a, T>>() {
+                let iter: slice::Iter<'a, T> = unsafe { cast_same(iter) };
+                unsafe { self.append_elements(iter.as_slice()) }
+                return;
+            }
+        }
+        self.spec_extend(iter)
     }
 }
 
@@ -2891,11 +4511,8 @@ impl<'a, I: Iterator> DoubleEndedIterator for Splice<'a, I> {
     }
 }
 
-#[cfg(feature = "unstable")]
 impl<'a, I: Iterator> ExactSizeIterator for Splice<'a, I> {}
 
-// TODO: re-evaluate this
-#[cfg(feature = "unstable")]
 impl<'a, I: Iterator> iter::FusedIterator for Splice<'a, I> {}
 
 impl<'a, I: Iterator> Drop for Splice<'a, I> {
@@ -2943,118 +4560,650 @@ impl<'a, I: Iterator> Drop for Splice<'a, I> {
         // Let `Drain::drop` move the tail back if necessary and restore
         // `deq.tail`.
     }
-}
+}
+
+/// Private helper methods for `Splice::drop`
+impl<'a, T> Drain<'a, T> {
+    /// The range from `self.deq.tail` to `self.tail()_start` contains elements
+    /// that have been moved out.
+    /// Fill that range as much as possible with new elements from the
+    /// `replace_with` iterator. Return whether we filled the entire
+    /// range. (`replace_with.next()` didn’t return `None`.)
+    unsafe fn fill<I: Iterator<Item = T>>(
+        &mut self, replace_with: &mut I,
+    ) -> bool {
+        let deq = self.deq.as_mut();
+        let range_start = deq.len();
+        let range_end = self.tail_start;
+        let range_slice = slice::from_raw_parts_mut(
+            deq.as_mut_ptr().add(range_start),
+            range_end - range_start,
+        );
+
+        for place in range_slice {
+            if let Some(new_item) = replace_with.next() {
+                ptr::write(place, new_item);
+                deq.move_tail_unchecked(1);
+            } else {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Make room for inserting more elements before the tail by moving
+    /// either the head or the tail, whichever is shorter.
+    unsafe fn move_tail_unchecked(&mut self, extra_capacity: usize) {
+        let deq = self.deq.as_mut();
+        let start = deq.len();
+        let used_capacity = self.tail_start + self.tail_len;
+        if used_capacity + extra_capacity > deq.capacity() {
+            // Move the hole and the tail to the new buffer as well, and
+            // leave the extra capacity in front of the tail:
+            deq.reallocate_with_gap(
+                used_capacity + extra_capacity,
+                used_capacity,
+                self.tail_start,
+                extra_capacity,
+            )
+            .expect("oom");
+        } else if start < self.tail_len {
+            // Move the head segment `extra_capacity` elements to the front:
+            deq.move_head_unchecked(-(extra_capacity as isize));
+            let src = deq.as_ptr().add(extra_capacity);
+            let dst = deq.as_mut_ptr();
+            ptr::copy(src, dst, start);
+            deq.move_tail_unchecked(-(extra_capacity as isize));
+        } else {
+            let src = deq.as_ptr().add(self.tail_start);
+            let dst = deq.as_mut_ptr().add(self.tail_start + extra_capacity);
+            ptr::copy(src, dst, self.tail_len);
+        }
+        self.tail_start += extra_capacity;
+    }
+}
+
+/// An iterator produced by calling `extract_if` on `SliceDeque`.
+#[derive(Debug)]
+pub struct ExtractIf<'a, T: 'a, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    /// The deque being filtered; its length is the start of the range.
+    deq: &'a mut SliceDeque<T>,
+    /// Index of the next element to test.
+    idx: usize,
+    /// End of the range.
+    end: usize,
+    /// Number of removed elements.
+    del: usize,
+    /// Length of the deque before filtering.
+    old_len: usize,
+    /// Start of the run of kept elements that follows the last removed
+    /// element. These elements have not been moved yet.
+    run_start: usize,
+    /// The predicate.
+    pred: F,
+}
+
+impl<'a, T, F> Iterator for ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        unsafe {
+            while self.idx != self.end {
+                let i = self.idx;
+                self.idx += 1;
+                let v = slice::from_raw_parts_mut(
+                    self.deq.as_mut_ptr(),
+                    self.old_len,
+                );
+                if (self.pred)(&mut v[i]) {
+                    let item = ptr::read(&v[i]);
+                    if self.del > 0 {
+                        // Close the hole in front of the current run of kept
+                        // elements. This is safe because self.deq does not
+                        // contain them thus its elements will not have
+                        // Drop::drop called on them in the event of a panic.
+                        let run_start = self.run_start;
+                        let src: *const T = &v[run_start];
+                        let dst: *mut T = &mut v[run_start - self.del];
+                        ptr::copy(src, dst, i - run_start);
+                    }
+                    self.del += 1;
+                    self.run_start = self.idx;
+                    return Some(item);
+                }
+            }
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.idx))
+    }
+}
+
+impl<'a, T, F> Drop for ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        unsafe {
+            // The kept elements are split by a hole of `del` elements into a
+            // compacted head and the rest of the elements: close the hole by
+            // moving the shorter one.
+            let hidden = (self.old_len - self.deq.len()) as isize;
+            let head_len = self.run_start - self.del;
+            let tail_len = self.old_len - self.run_start;
+            if self.del == 0 {
+                self.deq.move_tail_unchecked(hidden);
+            } else if head_len < tail_len {
+                let src = self.deq.as_ptr();
+                let dst = self.deq.as_mut_ptr().add(self.del);
+                ptr::copy(src, dst, head_len);
+                self.deq.move_tail_unchecked(hidden);
+                self.deq.move_head_unchecked(self.del as isize);
+            } else {
+                let src = self.deq.as_ptr().add(self.run_start);
+                let dst = self.deq.as_mut_ptr().add(head_len);
+                ptr::copy(src, dst, tail_len);
+                self.deq.move_tail_unchecked(hidden - self.del as isize);
+            }
+        }
+    }
+}
+
+/// An iterator produced by calling `drain_filter` on `SliceDeque`.
+///
+/// Unlike `ExtractIf`, dropping it removes all the remaining elements
+/// for which the predicate returns `true`.
+#[derive(Debug)]
+pub struct DrainFilter<'a, T: 'a, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    /// The underlying iterator.
+    inner: ExtractIf<'a, T, F>,
+}
+
+impl<'a, T, F> Iterator for DrainFilter<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T, F> Drop for DrainFilter<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
+
+/// A cursor over a `SliceDeque` with editing operations.
+///
+/// A cursor points either to an element of the deque or to a "ghost"
+/// non-element that sits between the back and the front of the deque. Moving
+/// past the back of the deque reaches the ghost, and moving past the ghost
+/// wraps around to the front.
+///
+/// Insertions and removals shift the elements on the shorter side of the
+/// cursor, just like `SliceDeque::insert` and `SliceDeque::remove`.
+///
+/// This `struct` is created by the [`cursor_front_mut`] and
+/// [`cursor_back_mut`] methods on [`SliceDeque`].
+///
+/// [`cursor_front_mut`]: struct.SliceDeque.html#method.cursor_front_mut
+/// [`cursor_back_mut`]: struct.SliceDeque.html#method.cursor_back_mut
+/// [`SliceDeque`]: struct.SliceDeque.html
+pub struct CursorMut<'a, T: 'a> {
+    /// Index of the current element; `deq.len()` for the ghost non-element.
+    index: usize,
+    /// The deque being edited.
+    deq: &'a mut SliceDeque<T>,
+}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for CursorMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CursorMut")
+            .field(&self.deq)
+            .field(&self.index())
+            .finish()
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Is the cursor pointing to the ghost non-element?
+    #[inline]
+    fn is_ghost(&self) -> bool {
+        self.index == self.deq.len()
+    }
+
+    /// Index of the current element, or `None` if the cursor points to the
+    /// ghost non-element.
+    #[inline]
+    pub fn index(&self) -> Option<usize> {
+        if self.is_ghost() {
+            None
+        } else {
+            Some(self.index)
+        }
+    }
+
+    /// Moves the cursor to the next element.
+    ///
+    /// If the cursor points to the ghost non-element, this moves it to the
+    /// front of the deque. If it points to the back of the deque, this moves it
+    /// to the ghost non-element.
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.index = if self.is_ghost() { 0 } else { self.index + 1 };
+    }
+
+    /// Moves the cursor to the previous element.
+    ///
+    /// If the cursor points to the ghost non-element, this moves it to the
+    /// back of the deque. If it points to the front of the deque, this moves
+    /// it to the ghost non-element.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.index = if self.index == 0 {
+            self.deq.len()
+        } else {
+            self.index - 1
+        };
+    }
+
+    /// Returns a reference to the current element, or `None` if the cursor
+    /// points to the ghost non-element.
+    #[inline]
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.deq.get_mut(self.index)
+    }
+
+    /// Returns a reference to the next element, without moving the cursor.
+    ///
+    /// If the cursor points to the ghost non-element, this returns the front
+    /// of the deque. If it points to the back, this returns `None`.
+    #[inline]
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = if self.is_ghost() { 0 } else { self.index + 1 };
+        self.deq.get_mut(next)
+    }
+
+    /// Returns a reference to the previous element, without moving the
+    /// cursor.
+    ///
+    /// If the cursor points to the ghost non-element, this returns the back of
+    /// the deque. If it points to the front, this returns `None`.
+    #[inline]
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        match self.index.checked_sub(1) {
+            Some(prev) => self.deq.get_mut(prev),
+            None => None,
+        }
+    }
+
+    /// Inserts `item` before the current element.
+    ///
+    /// If the cursor points to the ghost non-element, `item` is inserted at
+    /// the back of the deque. The cursor keeps pointing to the same element.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn insert_before(&mut self, item: T) {
+        self.deq.insert(self.index, item);
+        self.index += 1;
+    }
+
+    /// Inserts `item` after the current element.
+    ///
+    /// If the cursor points to the ghost non-element, `item` is inserted at
+    /// the front of the deque. The cursor keeps pointing to the same element.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn insert_after(&mut self, item: T) {
+        if self.is_ghost() {
+            self.deq.insert(0, item);
+            self.index += 1;
+        } else {
+            self.deq.insert(self.index + 1, item);
+        }
+    }
+
+    /// Removes the current element and returns it, moving the cursor to the
+    /// next element.
+    ///
+    /// If the cursor points to the ghost non-element, nothing is removed and
+    /// `None` is returned.
+    #[inline]
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.is_ghost() {
+            None
+        } else {
+            Some(self.deq.remove(self.index))
+        }
+    }
+
+    /// Splits the deque after the current element, returning a new deque
+    /// containing everything after the cursor.
+    ///
+    /// If the cursor points to the ghost non-element, the whole content of
+    /// the deque is moved.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn split_after(&mut self) -> SliceDeque<T> {
+        if self.is_ghost() {
+            let other = self.deq.split_off(0);
+            self.index = 0;
+            other
+        } else {
+            self.deq.split_off(self.index + 1)
+        }
+    }
+
+    /// Splits the deque before the current element, returning a new deque
+    /// containing everything before the cursor.
+    ///
+    /// If the cursor points to the ghost non-element, the whole content of
+    /// the deque is moved. Afterwards, the current element is at the front of
+    /// the deque.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn split_before(&mut self) -> SliceDeque<T> {
+        let at = self.index;
+        let mut other = SliceDeque::with_capacity(at);
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.deq.as_ptr(),
+                other.as_mut_ptr(),
+                at,
+            );
+            other.move_tail_unchecked(at as isize);
+            self.deq.move_head_unchecked(at as isize);
+        }
+        self.index = 0;
+        other
+    }
+}
+
+/// A writer over the spare capacity at the back of a `SliceDeque`.
+///
+/// It keeps track of how many elements have been written, which are added
+/// to the deque when the writer is dropped, even if the closure passed to
+/// [`write_back_with`] panics.
+///
+/// [`write_back_with`]: struct.SliceDeque.html#method.write_back_with
+pub struct SpareWriter<'a, T: 'a> {
+    /// Number of elements written after the back of the deque.
+    written: usize,
+    /// The deque being written to.
+    deq: &'a mut SliceDeque<T>,
+}
+
+impl<'a, T: 'a> SpareWriter<'a, T> {
+    /// Number of elements written.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.written
+    }
+
+    /// Is nothing written yet?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    /// Number of elements that can still be written.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.deq.capacity() - self.deq.len() - self.written
+    }
+
+    /// The elements written so far.
+    #[inline]
+    pub fn written_mut(&mut self) -> &mut [T] {
+        let len = self.deq.len();
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.deq.as_mut_ptr().add(len),
+                self.written,
+            )
+        }
+    }
+
+    /// The memory that has not been written yet.
+    ///
+    /// After initializing the first `n` elements of this slice, call
+    /// `advance(n)` to add them to the elements written.
+    #[inline]
+    pub fn unwritten_mut(&mut self) -> &mut [mem::MaybeUninit<T>] {
+        let written = self.written;
+        &mut self.deq.spare_back_mut()[written..]
+    }
+
+    /// Adds the first `n` elements of `unwritten_mut()` to the elements
+    /// written.
+    ///
+    /// # Safety
+    ///
+    /// The first `n` elements of `unwritten_mut()` must have been
+    /// initialized.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is larger than `remaining()`.
+    #[inline]
+    pub unsafe fn advance(&mut self, n: usize) {
+        assert!(
+            n <= self.remaining(),
+            "cannot advance {} elements, only {} remain",
+            n,
+            self.remaining()
+        );
+        self.written += n;
+    }
 
-/// Private helper methods for `Splice::drop`
-impl<'a, T> Drain<'a, T> {
-    /// The range from `self.deq.tail` to `self.tail()_start` contains elements
-    /// that have been moved out.
-    /// Fill that range as much as possible with new elements from the
-    /// `replace_with` iterator. Return whether we filled the entire
-    /// range. (`replace_with.next()` didn’t return `None`.)
-    unsafe fn fill<I: Iterator<Item = T>>(
-        &mut self, replace_with: &mut I,
-    ) -> bool {
-        let deq = self.deq.as_mut();
-        let range_start = deq.len();
-        let range_end = self.tail_start;
-        let range_slice = slice::from_raw_parts_mut(
-            deq.as_mut_ptr().add(range_start),
-            range_end - range_start,
-        );
+    /// Writes `value` after the elements written so far, or returns it if
+    /// the spare capacity is exhausted.
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        match self.unwritten_mut().first_mut() {
+            None => return Err(value),
+            Some(slot) => *slot = mem::MaybeUninit::new(value),
+        }
+        self.written += 1;
+        Ok(())
+    }
 
-        for place in range_slice {
-            if let Some(new_item) = replace_with.next() {
-                ptr::write(place, new_item);
-                deq.move_tail_unchecked(1);
-            } else {
-                return false;
-            }
+    /// Copies as many elements of `other` as fit after the elements written
+    /// so far, and returns how many were copied.
+    #[inline]
+    pub fn extend_from_slice(&mut self, other: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        let n = cmp::min(other.len(), self.remaining());
+        unsafe {
+            ptr::copy_nonoverlapping(
+                other.as_ptr(),
+                self.unwritten_mut().as_mut_ptr() as *mut T,
+                n,
+            );
+            self.advance(n);
         }
-        true
+        n
     }
+}
 
-    /// Make room for inserting more elements before the tail.
-    unsafe fn move_tail_unchecked(&mut self, extra_capacity: usize) {
-        let deq = self.deq.as_mut();
-        let used_capacity = self.tail_start + self.tail_len;
-        deq.reserve_capacity(used_capacity + extra_capacity)
-            .expect("oom");
+impl<'a, T: 'a> Drop for SpareWriter<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { self.deq.commit_back(self.written) }
+    }
+}
 
-        let new_tail_start = self.tail_start + extra_capacity;
-        let src = deq.as_ptr().add(self.tail_start);
-        let dst = deq.as_mut_ptr().add(new_tail_start);
-        ptr::copy(src, dst, self.tail_len);
-        self.tail_start = new_tail_start;
+impl<'a, T: 'a> fmt::Debug for SpareWriter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SpareWriter")
+            .field("written", &self.written)
+            .field("remaining", &self.remaining())
+            .finish()
     }
 }
 
-/// An iterator produced by calling `drain_filter` on `SliceDeque`.
-#[derive(Debug)]
-pub struct DrainFilter<'a, T: 'a, F>
-where
-    F: FnMut(&mut T) -> bool,
-{
-    /// TODO: docs
+/// A speculative sequence of `push_back` and `pop_front` calls on a
+/// `SliceDeque`.
+///
+/// The checkpoint derefs into a slice of the elements that have not been
+/// popped. Dropping it without calling [`commit`] rolls back the pushes and
+/// pops, but not the changes made to the elements through that slice.
+///
+/// See [`checkpoint`].
+///
+/// [`commit`]: #method.commit
+/// [`checkpoint`]: struct.SliceDeque.html#method.checkpoint
+pub struct Checkpoint<'a, T: 'a> {
+    /// Number of elements popped from the front of the deque, which are
+    /// still stored in the deque.
+    consumed: usize,
+    /// Length of the deque when the checkpoint was taken.
+    len: usize,
+    /// The deque being modified.
     deq: &'a mut SliceDeque<T>,
-    /// TODO: docs
-    idx: usize,
-    /// TODO: docs
-    del: usize,
-    /// TODO: docs
-    old_len: usize,
-    /// TODO: docs
-    pred: F,
 }
 
-impl<'a, T, F> Iterator for DrainFilter<'a, T, F>
-where
-    F: FnMut(&mut T) -> bool,
-{
-    type Item = T;
+impl<'a, T: 'a> Checkpoint<'a, T> {
+    /// Appends `value` to the deque.
+    ///
+    /// # Panics
+    ///
+    /// If the new capacity overflows `usize` or on OOM.
+    #[inline]
+    pub fn push_back(&mut self, value: T) {
+        self.deq.push_back(value);
+    }
 
-    fn next(&mut self) -> Option<T> {
-        unsafe {
-            while self.idx != self.old_len {
-                let i = self.idx;
-                self.idx += 1;
-                let v = slice::from_raw_parts_mut(
-                    self.deq.as_mut_ptr(),
-                    self.old_len,
-                );
-                if (self.pred)(&mut v[i]) {
-                    self.del += 1;
-                    return Some(ptr::read(&v[i]));
-                } else if self.del > 0 {
-                    let del = self.del;
-                    let src: *const T = &v[i];
-                    let dst: *mut T = &mut v[i - del];
-                    // This is safe because self.deq has length 0
-                    // thus its elements will not have Drop::drop
-                    // called on them in the event of a panic.
-                    ptr::copy_nonoverlapping(src, dst, 1);
-                }
-            }
-            None
-        }
+    /// Pops the first element and returns a reference to it, or `None` if
+    /// there are no elements left.
+    ///
+    /// The element is dropped when the checkpoint is committed.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<&T> {
+        let elem = self.deq.get(self.consumed)?;
+        self.consumed += 1;
+        Some(elem)
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, Some(self.old_len - self.idx))
+    /// Pops the first `n` elements and returns them, or returns `None` if
+    /// there are fewer than `n` elements left.
+    ///
+    /// The elements are dropped when the checkpoint is committed.
+    #[inline]
+    pub fn pop_front_n(&mut self, n: usize) -> Option<&[T]> {
+        let start = self.consumed;
+        let end = start.checked_add(n)?;
+        let elems = self.deq.get(start..end)?;
+        self.consumed = end;
+        Some(elems)
+    }
+
+    /// Elements popped since the checkpoint was taken.
+    #[inline]
+    pub fn consumed(&self) -> &[T] {
+        unsafe { self.deq.get_unchecked(..self.consumed) }
+    }
+
+    /// Keeps the changes: drops the popped elements.
+    #[inline]
+    pub fn commit(mut self) {
+        let len = self.deq.len() - self.consumed;
+        self.consumed = 0;
+        self.len = self.deq.len();
+        self.deq.truncate_front(len);
     }
+
+    /// Undoes the pushes and pops: restores the popped elements and drops
+    /// the pushed ones.
+    ///
+    /// Elements modified in place keep their new values.
+    #[inline]
+    pub fn rollback(self) {}
 }
 
-impl<'a, T, F> Drop for DrainFilter<'a, T, F>
+impl<'a, T: 'a> Drop for Checkpoint<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.deq.truncate_back(self.len);
+    }
+}
+
+impl<'a, T: 'a> ops::Deref for Checkpoint<'a, T> {
+    type Target = [T];
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.deq.get_unchecked(self.consumed..) }
+    }
+}
+
+impl<'a, T: 'a> ops::DerefMut for Checkpoint<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.deq.get_unchecked_mut(self.consumed..) }
+    }
+}
+
+impl<'a, A: 'a, B> PartialEq<[B]> for Checkpoint<'a, A>
 where
-    F: FnMut(&mut T) -> bool,
+    A: PartialEq<B>,
 {
-    fn drop(&mut self) {
-        for _ in self.by_ref() {}
+    #[inline]
+    fn eq(&self, other: &[B]) -> bool {
+        self[..] == other[..]
+    }
+}
 
-        unsafe {
-            let new_len = self.old_len - self.del;
-            self.deq.move_tail_unchecked(new_len as isize);
-        }
+impl<'a, A: 'a, B, const N: usize> PartialEq<[B; N]> for Checkpoint<'a, A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &[B; N]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<'a, T: 'a + fmt::Debug> fmt::Debug for Checkpoint<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Checkpoint")
+            .field("consumed", &self.consumed())
+            .field("remaining", &&**self)
+            .finish()
     }
 }
 
@@ -3140,7 +5289,7 @@ mod tests {
     use super::SliceDeque;
     use std::cell::RefCell;
     use std::rc::Rc;
-    use std::{collections, fmt, hash, mem};
+    use std::{cmp, collections, fmt, hash, mem};
 
     #[derive(Clone, Debug)]
     struct WithDrop {
@@ -4140,14 +6289,764 @@ mod tests {
         assert_eq!(v, &[1, 2]);
     }
 
-    /* into_boxed_slice probably can't be supported portably
     #[test]
+    fn drain_moves_shorter_side() {
+        let mut deq: SliceDeque<usize> = (0..100).collect();
+        let ptr = deq.as_ptr();
+        assert!(deq.drain(..10).eq(0..10));
+        assert_eq!(deq.as_ptr(), unsafe { ptr.add(10) });
+        assert!(deq.drain(2..5).eq(12..15));
+        assert_eq!(deq.as_ptr(), unsafe { ptr.add(13) });
+        assert!(deq.drain(82..87).eq(95..100));
+        assert_eq!(deq.as_ptr(), unsafe { ptr.add(13) });
+        assert!(deq.drain(70..72).eq(83..85));
+        assert_eq!(deq.as_ptr(), unsafe { ptr.add(13) });
+        let expected: SliceDeque<usize> =
+            (10..12).chain(15..83).chain(85..95).collect();
+        assert_eq!(deq, expected);
+
+        let ptr = deq.as_ptr();
+        let removed: SliceDeque<_> = deq
+            .extract_if(.., |x| *x < 12 || *x == 16 || *x == 18)
+            .collect();
+        assert_eq!(removed, &[10, 11, 16, 18]);
+        assert_eq!(deq.as_ptr(), unsafe { ptr.add(4) });
+        let ptr = deq.as_ptr();
+        let removed: SliceDeque<_> =
+            deq.extract_if(.., |x| *x == 90).collect();
+        assert_eq!(removed, &[90]);
+        assert_eq!(deq.as_ptr(), ptr);
+        let expected: SliceDeque<usize> = sdeq![15, 17]
+            .into_iter()
+            .chain(19..83)
+            .chain(85..90)
+            .chain(91..95)
+            .collect();
+        assert_eq!(deq, expected);
+
+        // Dropping a partially consumed drain still drops every element:
+        let rc = Rc::new(());
+        let mut deq: SliceDeque<_> = (0..10).map(|_| rc.clone()).collect();
+        mem::drop(deq.drain(1..3).next());
+        assert_eq!(Rc::strong_count(&rc), 9);
+        mem::drop(deq.drain(6..7));
+        assert_eq!(Rc::strong_count(&rc), 8);
+        assert_eq!(deq.extract_if(.., |_| true).take(2).count(), 2);
+        assert_eq!(Rc::strong_count(&rc), 6);
+        mem::drop(deq);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn extract_if() {
+        // Only the range is tested, and the hole is closed from the front
+        let mut deq: SliceDeque<usize> = (0..100).collect();
+        let ptr = deq.as_ptr();
+        let removed: SliceDeque<_> =
+            deq.extract_if(2..10, |x| *x % 2 == 0).collect();
+        assert_eq!(removed, &[2, 4, 6, 8]);
+        assert_eq!(deq.as_ptr(), unsafe { ptr.add(4) });
+        let expected: SliceDeque<usize> =
+            (0..2).chain(vec![3, 5, 7, 9]).chain(10..100).collect();
+        assert_eq!(deq, expected);
+
+        // ... or from the back
+        let ptr = deq.as_ptr();
+        let removed: SliceDeque<_> =
+            deq.extract_if(90..=94, |x| *x > 95).collect();
+        assert_eq!(removed, &[96, 97, 98]);
+        assert_eq!(deq.as_ptr(), ptr);
+        assert_eq!(deq.len(), 93);
+        assert_eq!(&deq[88..], &[92, 93, 94, 95, 99]);
+
+        // The remaining elements are kept if the iterator is dropped early
+        let mut deq: SliceDeque<usize> = (0..10).collect();
+        let mut it = deq.extract_if(1.., |x| *x % 3 == 0);
+        assert_eq!(it.next(), Some(3));
+        mem::drop(it);
+        assert_eq!(deq, &[0, 1, 2, 4, 5, 6, 7, 8, 9]);
+        mem::drop(deq.extract_if(.., |_| true));
+        assert_eq!(deq.len(), 9);
+
+        // ... and when the predicate panics
+        let rc = Rc::new(());
+        let mut deq: SliceDeque<_> = (0..10).map(|_| rc.clone()).collect();
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut n = 0;
+            deq.extract_if(.., |_| {
+                n += 1;
+                if n == 5 {
+                    panic!("predicate failed");
+                }
+                n % 2 == 0
+            })
+            .for_each(mem::drop)
+        }));
+        assert!(r.is_err());
+        assert_eq!(deq.len(), 8);
+        assert_eq!(Rc::strong_count(&rc), 9);
+    }
+
+    #[test]
+    fn retain_ends_while() {
+        let mut deq: SliceDeque<usize> = (0..10).collect();
+        deq.retain_front_while(|&x| x >= 3);
+        deq.retain_back_while(|&x| x < 8);
+        assert_eq!(deq, &[3, 4, 5, 6, 7]);
+        deq.retain_front_while(|_| true);
+        deq.retain_back_while(|_| true);
+        assert_eq!(deq.len(), 5);
+        deq.retain_front_while(|_| false);
+        assert!(deq.is_empty());
+        deq.retain_back_while(|_| false);
+        assert!(deq.is_empty());
+    }
+
+    #[test]
+    fn splice_moves_shorter_side() {
+        // Grows the hole by moving the head:
+        let mut deq: SliceDeque<usize> = (0..10).collect();
+        deq.pop_front();
+        let ptr = deq.as_ptr();
+        let removed: SliceDeque<_> =
+            deq.splice(0..1, vec![10, 11].into_iter()).collect();
+        assert_eq!(removed, &[1]);
+        assert_eq!(deq.as_ptr(), unsafe { ptr.sub(1) });
+        assert_eq!(deq, &[10, 11, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        // Grows the hole by moving the tail:
+        let mut deq: SliceDeque<usize> = (0..10).collect();
+        deq.pop_back();
+        let ptr = deq.as_ptr();
+        let removed: SliceDeque<_> =
+            deq.splice(8..9, vec![10, 11].into_iter()).collect();
+        assert_eq!(removed, &[8]);
+        assert_eq!(deq.as_ptr(), ptr);
+        assert_eq!(deq, &[0, 1, 2, 3, 4, 5, 6, 7, 10, 11]);
+
+        // Reallocates while both the head and tail are non-empty, with an
+        // inexact size hint:
+        for &at in &[1, 5, 10] {
+            let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+            let cap = deq.capacity();
+            deq.extend(0..cap);
+            let ins: SliceDeque<usize> = (cap..2 * cap).collect();
+            let removed: SliceDeque<_> = deq
+                .splice(at..at + 1, ins.iter().cloned().filter(|_| true))
+                .collect();
+            assert_eq!(removed, &[at]);
+            let expected: SliceDeque<usize> =
+                (0..at).chain(cap..2 * cap).chain(at + 1..cap).collect();
+            assert_eq!(deq, expected);
+        }
+
+        // Splices at the end, where there is no tail to move:
+        let mut deq: SliceDeque<i32> = (0..5).collect();
+        let removed: SliceDeque<_> =
+            deq.splice(3.., vec![7, 8, 9, 10]).collect();
+        assert_eq!(removed, &[3, 4]);
+        assert_eq!(deq, &[0, 1, 2, 7, 8, 9, 10]);
+        deq.splice(7.., (0..3).filter(|_| true));
+        assert_eq!(deq, &[0, 1, 2, 7, 8, 9, 10, 0, 1, 2]);
+    }
+
+    #[test]
+    fn front_insertion() {
+        let mut deq: SliceDeque<usize> = SliceDeque::new();
+        deq.extend_front_from_slice(&[3, 4]);
+        assert_eq!(deq, &[3, 4]);
+        deq.extend_front(1..3);
+        assert_eq!(deq, &[1, 2, 3, 4]);
+        let mut other = sdeq![0];
+        deq.prepend(&mut other);
+        assert_eq!(deq, &[0, 1, 2, 3, 4]);
+        assert!(other.is_empty());
+        deq.prepend(&mut other);
+        deq.extend_front(None);
+        deq.extend_front_from_slice(&[]);
+        assert_eq!(deq, &[0, 1, 2, 3, 4]);
+
+        // Iterators whose lower bound is not exact:
+        let mut deq: SliceDeque<usize> = (10..20).collect();
+        deq.extend_front((0..10).filter(|_| true));
+        assert!(deq.iter().cloned().eq(0..20));
+        let mut deq: SliceDeque<usize> = (10..20).collect();
+        deq.extend_front((0..5).chain((5..10).filter(|_| true)));
+        assert!(deq.iter().cloned().eq(0..20));
+        let mut deq: SliceDeque<usize> = SliceDeque::new();
+        deq.extend_front((0..3).filter(|_| true));
+        assert_eq!(deq, &[0, 1, 2]);
+        deq.extend_front((0..0).filter(|_| true));
+        assert_eq!(deq, &[0, 1, 2]);
+        let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+        let cap = deq.capacity();
+        deq.extend(cap..2 * cap);
+        deq.extend_front((0..cap / 2).chain((cap / 2..cap).filter(|_| true)));
+        assert!(deq.iter().cloned().eq(0..2 * cap));
+
+        // Growing a full deque, and wrapping the head around the buffer:
+        let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+        let cap = deq.capacity();
+        deq.extend(cap..2 * cap);
+        deq.extend_front(0..cap);
+        assert!(deq.iter().cloned().eq(0..2 * cap));
+        let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+        deq.extend(cap - 2..cap);
+        deq.extend_front_from_slice(&(0..cap - 2).collect::<SliceDeque<_>>());
+        assert_eq!(deq.capacity(), cap);
+        assert!(deq.iter().cloned().eq(0..cap));
+
+        let rc = Rc::new(());
+        let mut deq = sdeq![rc.clone()];
+        deq.extend_front(vec![rc.clone(), rc.clone()]);
+        deq.extend_front(Some(rc.clone()).into_iter().filter(|_| true));
+        deq.extend_front_from_slice(&[rc.clone()]);
+        let mut other = sdeq![rc.clone()];
+        deq.prepend(&mut other);
+        assert_eq!(Rc::strong_count(&rc), 7);
+        mem::drop((deq, other));
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        // Elements whose size does not divide the size of the buffer:
+        let mut deq: SliceDeque<[u8; 3]> = sdeq![[4, 4, 4]];
+        let mut other = sdeq![[3, 3, 3]];
+        deq.prepend(&mut other);
+        deq.extend_front(vec![[1, 1, 1], [2, 2, 2]]);
+        deq.extend_front(Some([0, 0, 0]).into_iter().filter(|_| true));
+        assert_eq!(
+            deq,
+            &[[0, 0, 0], [1, 1, 1], [2, 2, 2], [3, 3, 3], [4, 4, 4]]
+        );
+    }
+
+    #[test]
+    fn spare_capacity() {
+        let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+        let cap = deq.capacity();
+        deq.push_back(2);
+        assert_eq!(deq.spare_back_mut().len(), cap - 1);
+        {
+            let spare = deq.spare_front_mut();
+            spare[cap - 3] = mem::MaybeUninit::new(0);
+            spare[cap - 2] = mem::MaybeUninit::new(1);
+        }
+        unsafe { deq.commit_front(2) };
+        deq.spare_back_mut()[0] = mem::MaybeUninit::new(3);
+        unsafe { deq.commit_back(1) };
+        assert_eq!(deq, &[0, 1, 2, 3]);
+
+        // Elements whose size does not divide the size of the buffer:
+        let mut bytes: SliceDeque<[u8; 3]> = sdeq![[2, 2, 2]];
+        {
+            let spare = bytes.spare_front_mut();
+            let n = spare.len();
+            spare[n - 2] = mem::MaybeUninit::new([0, 0, 0]);
+            spare[n - 1] = mem::MaybeUninit::new([1, 1, 1]);
+        }
+        unsafe { bytes.commit_front(2) };
+        assert_eq!(bytes, &[[0, 0, 0], [1, 1, 1], [2, 2, 2]]);
+
+        let written = deq.write_back_with(|w| {
+            let mut i = 4;
+            while w.push(i).is_ok() {
+                i += 1;
+            }
+            assert_eq!(w.push(0), Err(0));
+            assert_eq!(w.remaining(), 0);
+            w.len()
+        });
+        assert_eq!(written, cap - 4);
+        assert!(deq.is_full());
+        assert!(deq.iter().cloned().eq(0..cap));
+        assert_eq!(deq.write_back_with(|w| w.extend_from_slice(&[1])), 0);
+
+        // Writing through the unwritten memory:
+        let mut deq: SliceDeque<u8> = sdeq![1];
+        deq.write_back_with(|w| {
+            w.unwritten_mut()[0] = mem::MaybeUninit::new(2);
+            unsafe { w.advance(1) };
+            assert_eq!(w.extend_from_slice(&[3, 4]), 2);
+            w.written_mut()[0] = 20;
+            assert_eq!(w.written_mut(), &[20, 3, 4]);
+        });
+        assert_eq!(deq, &[1, 20, 3, 4]);
+
+        // The elements written before a panic are added to the deque:
+        let rc = Rc::new(());
+        let mut deq = sdeq![rc.clone()];
+        deq.reserve(2);
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            deq.write_back_with(|w| {
+                assert!(w.push(rc.clone()).is_ok());
+                panic!("decoder failed");
+            })
+        }));
+        assert!(r.is_err());
+        assert_eq!(deq.len(), 2);
+        assert_eq!(Rc::strong_count(&rc), 3);
+        mem::drop(deq);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        // Advancing past the spare capacity panics:
+        let mut deq: SliceDeque<u8> = SliceDeque::new();
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            deq.write_back_with(|w| unsafe { w.advance(w.remaining() + 1) })
+        }));
+        assert!(r.is_err());
+        assert!(deq.is_empty());
+    }
+
+    #[test]
+    fn rotate() {
+        let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+        let cap = deq.capacity();
+        deq.extend(0..cap);
+        let ptr = deq.as_ptr();
+        assert_eq!(deq.rotated_view(cap), deq.as_slice());
+        assert!(deq.rotated_view(3).iter().cloned().eq((3..cap).chain(0..3)));
+        deq.rotate_left(3);
+        assert_eq!(deq.as_ptr(), unsafe { ptr.add(3) });
+        assert!(deq.iter().cloned().eq((3..cap).chain(0..3)));
+        deq.rotate_right(5);
+        assert!(deq.iter().cloned().eq((cap - 2..cap).chain(0..cap - 2)));
+        deq.rotate_left(2);
+        assert_eq!(deq.as_ptr(), ptr);
+        assert!(deq.iter().cloned().eq(0..cap));
+
+        // Not full: move the shorter side
+        for &n in &[0, 1, 5, 9, 10] {
+            let mut deq: SliceDeque<usize> = (0..10).collect();
+            deq.rotate_left(n);
+            assert!(deq.iter().cloned().eq((n..10).chain(0..n)));
+            let mut deq: SliceDeque<usize> = (0..10).collect();
+            deq.rotate_right(n);
+            assert!(deq.iter().cloned().eq((10 - n..10).chain(0..10 - n)));
+        }
+
+        // Not enough spare capacity on either side:
+        let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+        deq.extend(0..cap - 1);
+        deq.rotate_left(cap / 2);
+        assert!(deq.iter().cloned().eq((cap / 2..cap - 1).chain(0..cap / 2)));
+        assert_eq!(sdeq![1, 2].rotated_view(0), &[1, 2]);
+
+        // Views of a deque whose head is not at the start of the buffer:
+        let mut deq: SliceDeque<u64> = SliceDeque::with_capacity(1);
+        let cap = deq.capacity();
+        deq.extend(0..cap as u64);
+        deq.rotate_left(cap - 1);
+        for &n in &[1, cap / 2, cap - 1] {
+            let view = deq.rotated_view(n);
+            assert_eq!(view.len(), cap);
+            let expected = (0..cap as u64).cycle().skip(cap - 1 + n);
+            assert!(view.iter().cloned().eq(expected.take(cap)));
+        }
+
+        // Rotating a full deque whose elements do not fill its buffer:
+        let mut deq: SliceDeque<[u8; 3]> = SliceDeque::with_capacity(1);
+        let cap = deq.capacity();
+        deq.extend((0..cap).map(|i| [i as u8, (i >> 8) as u8, 0]));
+        assert!(deq.is_full());
+        deq.rotate_left(5);
+        let expected = (0..cap).cycle().skip(5).take(cap);
+        assert!(deq.iter().cloned().eq(expected.map(|i| [
+            i as u8,
+            (i >> 8) as u8,
+            0
+        ])));
+
+        // Moving the back before the front of a deque that is not full and
+        // whose elements do not fill its buffer:
+        let mut deq: SliceDeque<[u8; 3]> = (0..10).map(|i| [i; 3]).collect();
+        deq.rotate_left(8);
+        assert!(deq.iter().cloned().eq((8..10).chain(0..8).map(|i| [i; 3])));
+        deq.push_front([42; 3]);
+        deq.push_back([43; 3]);
+        assert_eq!(deq.front(), Some(&[42; 3]));
+        assert_eq!(deq.back(), Some(&[43; 3]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rotated_view_not_full() {
+        let deq = sdeq![1, 2, 3];
+        deq.rotated_view(1);
+    }
+
+    #[test]
+    fn growth_policy() {
+        use super::GrowthPolicy;
+        let page = crate::mirrored::allocation_granularity();
+
+        let mut deq = SliceDeque::<u8>::new();
+        deq.set_growth_policy(GrowthPolicy::pages(1));
+        deq.extend((0..page * 2 + 1).map(|i| i as u8));
+        assert_eq!(deq.capacity(), page * 3);
+        let c = deq.clone();
+        assert_eq!(c.growth_policy(), GrowthPolicy::pages(1));
+
+        let policy = GrowthPolicy::doubling().max_capacity(page * 2);
+        deq.set_growth_policy(policy);
+        deq.truncate_back(page);
+        deq.shrink_to_fit();
+        assert_eq!(deq.capacity(), page);
+        deq.extend((0..page).map(|i| i as u8));
+        assert_eq!(deq.capacity(), page * 2);
+        match deq.try_reserve(1) {
+            Err(crate::AllocError::MaxCapacity) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert_eq!(deq.len(), page * 2);
+
+        let mut deq = SliceDeque::<u8>::new();
+        deq.set_growth_policy(GrowthPolicy::doubling().auto_shrink(4));
+        deq.extend((0..page * 8).map(|i| i as u8));
+        assert_eq!(deq.capacity(), page * 8);
+        deq.drain(..page * 6 + 1);
+        assert_eq!(deq.capacity(), page * 4);
+        assert_eq!(deq.len(), page * 2 - 1);
+        while deq.len() > page / 2 {
+            deq.pop_front();
+        }
+        assert_eq!(deq.capacity(), page * 2);
+        deq.truncate_back(1);
+        assert_eq!(deq.capacity(), page);
+        assert_eq!(deq, [(page * 7 + page / 2) as u8]);
+        deq.clear();
+        assert_eq!(deq.capacity(), page);
+        assert!(deq.pop_back().is_none());
+    }
+
+    #[test]
+    fn checkpoint() {
+        let counter = Rc::new(());
+        let mut deq: SliceDeque<_> =
+            (0..4).map(|i| (i, Rc::clone(&counter))).collect();
+        {
+            let mut cp = deq.checkpoint();
+            assert_eq!(cp.pop_front().map(|e| e.0), Some(0));
+            assert_eq!(cp.pop_front_n(2).map(<[_]>::len), Some(2));
+            assert!(cp.pop_front_n(2).is_none());
+            assert_eq!(cp.consumed().len(), 3);
+            assert_eq!(cp.len(), 1);
+            // Pushing may reallocate without affecting the popped elements:
+            for i in 4..1000 {
+                cp.push_back((i, Rc::clone(&counter)));
+            }
+            assert_eq!(cp[0].0, 3);
+            assert_eq!(Rc::strong_count(&counter), 1001);
+        }
+        assert_eq!(Rc::strong_count(&counter), 5);
+        assert_eq!(deq.iter().map(|e| e.0).collect::<Vec<_>>(), [0, 1, 2, 3]);
+
+        let mut cp = deq.checkpoint();
+        cp.pop_front();
+        cp.push_back((4, Rc::clone(&counter)));
+        cp.pop_front();
+        cp.rollback();
+        assert_eq!(deq.len(), 4);
+        assert_eq!(Rc::strong_count(&counter), 5);
+
+        let mut cp = deq.checkpoint();
+        cp.pop_front();
+        cp.push_back((4, Rc::clone(&counter)));
+        cp.pop_front();
+        cp.commit();
+        assert_eq!(deq.iter().map(|e| e.0).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(Rc::strong_count(&counter), 4);
+
+        let mut cp = deq.checkpoint();
+        while cp.pop_front().is_some() {}
+        cp.commit();
+        assert!(deq.is_empty());
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        // In-place changes are not undone:
+        let mut deq = sdeq![1, 2, 3];
+        let mut cp = deq.checkpoint();
+        assert_eq!(cp.pop_front(), Some(&1));
+        cp[0] = 20;
+        cp.push_back(4);
+        cp.rollback();
+        assert_eq!(deq, [1, 20, 3]);
+    }
+
+    #[test]
+    fn stable_iterator_traits() {
+        fn exact_fused<I: ExactSizeIterator + ::core::iter::FusedIterator>(
+            i: I,
+        ) -> usize {
+            i.len()
+        }
+        let mut deq: SliceDeque<u8> = (0..10).collect();
+        assert_eq!(exact_fused(deq.drain(2..5)), 3);
+        assert_eq!(exact_fused(deq.splice(..2, 20..22)), 2);
+        assert_eq!(deq, [20, 21, 5, 6, 7, 8, 9]);
+        assert_eq!(exact_fused(deq.clone().into_iter()), 7);
+
+        // Iterator whose size hint promises more than it yields:
+        struct Liar(u32);
+        impl Iterator for Liar {
+            type Item = u32;
+            fn next(&mut self) -> Option<u32> {
+                if self.0 == 0 {
+                    None
+                } else {
+                    self.0 -= 1;
+                    Some(self.0)
+                }
+            }
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (3, Some(3))
+            }
+        }
+        let mut deq = SliceDeque::new();
+        deq.extend(Liar(1));
+        assert_eq!(deq, [0]);
+        deq.extend(Liar(5));
+        assert_eq!(deq, [0, 4, 3, 2, 1, 0]);
+        deq.extend_from_slice(&[7, 8]);
+        assert_eq!(deq, [0, 4, 3, 2, 1, 0, 7, 8]);
+
+        let deq = super::from_elem(3_u8, 100);
+        assert!(deq.iter().all(|&x| x == 3));
+        assert_eq!(deq.len(), 100);
+    }
+
+    #[test]
+    fn stable_fast_paths() {
+        assert!(super::same_type::<&'static str, &str>());
+        assert!(!super::same_type::<u32, i32>());
+        assert!(super::is_primitive::<char>());
+        assert!(!super::is_primitive::<(u8, u8)>());
+
+        // Slices of `Copy` types are copied without calling `clone`:
+        #[derive(Copy, Debug, PartialEq)]
+        struct NoClone(u16);
+        #[allow(
+            clippy::expl_impl_clone_on_copy,
+            clippy::non_canonical_clone_impl
+        )]
+        impl Clone for NoClone {
+            fn clone(&self) -> Self {
+                panic!("cloned {:?}", self)
+            }
+        }
+        let src = [NoClone(1), NoClone(2), NoClone(3)];
+        let mut deq: SliceDeque<NoClone> = SliceDeque::new();
+        deq.extend(&src);
+        deq.extend(src[1..].iter());
+        assert_eq!(
+            deq,
+            [NoClone(1), NoClone(2), NoClone(3), NoClone(2), NoClone(3)]
+        );
+        let mut deq = sdeq![1_u32];
+        deq.extend_from_slice(&[2, 3]);
+        deq.extend(&[4, 5]);
+        assert_eq!(deq, [1, 2, 3, 4, 5]);
+
+        // The elements of an `IntoIter` are moved at once, and the buffer of
+        // an `IntoIter` that has not been advanced is reused:
+        let deq: SliceDeque<String> = (0..4).map(|i| i.to_string()).collect();
+        let ptr = deq.as_ptr();
+        let deq: SliceDeque<String> = deq.into_iter().collect();
+        assert_eq!(deq.as_ptr(), ptr);
+        let mut iter = deq.into_iter();
+        assert_eq!(iter.next().unwrap(), "0");
+        assert_eq!(iter.next_back().unwrap(), "3");
+        let mut deq = sdeq!["a".to_string()];
+        deq.extend(iter);
+        assert_eq!(deq, ["a", "1", "2"]);
+        let mut iter = deq.into_iter();
+        assert_eq!(iter.next().unwrap(), "a");
+        let deq: SliceDeque<String> = iter.collect();
+        assert_eq!(deq, ["1", "2"]);
+
+        // Integers whose bytes are all equal are written with a `memset`:
+        assert_eq!(super::fill_byte(&0_u64), Some(0));
+        assert_eq!(super::fill_byte(&-1_i32), Some(0xff));
+        assert_eq!(super::fill_byte(&0x0101_u16), Some(1));
+        assert_eq!(super::fill_byte(&0x0102_u16), None);
+        assert_eq!(super::fill_byte(&0.0_f32), None);
+        let zeros: SliceDeque<u64> = sdeq![0; 1000];
+        assert_eq!(zeros.len(), 1000);
+        assert!(zeros.iter().all(|&x| x == 0));
+        assert_eq!(sdeq![-1_i32; 3], [-1, -1, -1]);
+        assert_eq!(sdeq![0x0102_u16; 3], [0x0102, 0x0102, 0x0102]);
+        assert_eq!(sdeq![String::from("x"); 2], ["x", "x"]);
+        assert!(super::from_elem(7_i8, 0).is_empty());
+    }
+
+    #[test]
+    fn vec_parity() {
+        let mut deq = sdeq![0, 1, 2, 3];
+        deq.extend_from_within(..2);
+        deq.extend_from_within(5..=5);
+        deq.extend_from_within(6..6);
+        assert_eq!(deq, &[0, 1, 2, 3, 0, 1, 1]);
+
+        deq.retain_mut(|x| {
+            *x *= 2;
+            *x != 2
+        });
+        assert_eq!(deq, &[0, 4, 6, 0]);
+
+        let mut v = 0;
+        deq.resize_with(6, || {
+            v += 1;
+            v
+        });
+        assert_eq!(deq, &[0, 4, 6, 0, 1, 2]);
+        deq.resize_with(3, || unreachable!());
+        assert_eq!(deq, &[0, 4, 6]);
+
+        let cap = deq.capacity();
+        {
+            let (elems, spare) = deq.split_at_spare_mut();
+            assert_eq!(elems, &[0, 4, 6]);
+            assert_eq!(spare.len(), cap - 3);
+        }
+
+        // Round-trip through the raw parts of a deque whose head is not at
+        // the beginning of the buffer:
+        deq.push_front(1);
+        let (ptr, cap2, head, len) = deq.into_raw_parts();
+        assert_eq!((cap2, head, len), (cap, (cap - 1) * 4, 4));
+        let deq = unsafe {
+            let front = (ptr as *mut u8).add(head) as *mut i32;
+            let elems = std::slice::from_raw_parts_mut(front, len);
+            SliceDeque::from_raw_parts(ptr, cap, elems)
+        };
+        assert_eq!(deq, &[1, 0, 4, 6]);
+
+        // The same with elements whose size does not divide the buffer:
+        let mut deq: SliceDeque<[u8; 3]> = sdeq![[2; 3], [3; 3]];
+        deq.push_front([1; 3]);
+        let (ptr, cap, head, len) = deq.into_raw_parts();
+        let region_size = super::Buffer::<[u8; 3]>::size_in_bytes(cap * 2) / 2;
+        assert_eq!((head, len), (region_size - 3, 3));
+        let deq = unsafe {
+            let front = (ptr as *mut u8).add(head) as *mut [u8; 3];
+            let elems = std::slice::from_raw_parts_mut(front, len);
+            SliceDeque::from_raw_parts(ptr, cap, elems)
+        };
+        assert_eq!(deq, &[[1; 3], [2; 3], [3; 3]]);
+
+        let rc = Rc::new(());
+        let mut deq = SliceDeque::with_capacity(100_000);
+        deq.push_back(rc.clone());
+        deq.shrink_to(50_000);
+        assert!(deq.capacity() >= 50_000 && deq.capacity() < 100_000);
+        deq.shrink_to_fit();
+        assert!(deq.capacity() < 50_000);
+        assert_eq!(Rc::strong_count(&rc), 2);
+        assert_eq!(deq.make_contiguous().len(), 1);
+
+        let deq = sdeq![rc.clone(), rc.clone()];
+        let leaked = deq.leak();
+        assert_eq!(leaked.len(), 2);
+        assert_eq!(Rc::strong_count(&rc), 4);
+    }
+
+    #[test]
+    fn insert_grows_around_gap() {
+        for &at in &[0, 1, 5, 10] {
+            let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+            let cap = deq.capacity();
+            deq.extend(0..cap);
+            deq.insert(at, cap);
+            assert!(deq.capacity() > cap);
+            let expected: SliceDeque<usize> =
+                (0..at).chain(Some(cap)).chain(at..cap).collect();
+            assert_eq!(deq, expected);
+        }
+
+        for &at in &[0, 1, 5, 10] {
+            let mut deq: SliceDeque<usize> = SliceDeque::with_capacity(1);
+            let cap = deq.capacity();
+            deq.extend(0..cap - 2);
+            let ins: SliceDeque<usize> = (cap..cap + 2).collect();
+            // Inserting close to the front moves the head; the tail stays in
+            // place, modulo mirroring:
+            let offset = |p: &usize| {
+                p as *const usize as usize % (cap * mem::size_of::<usize>())
+            };
+            let tail_offset = offset(&deq[at]);
+            deq.insert_slice(at, &ins);
+            assert_eq!(deq.capacity(), cap);
+            assert_eq!(offset(&deq[at + 2]), tail_offset);
+            let ins: SliceDeque<usize> = (cap + 2..3 * cap).collect();
+            deq.insert_slice(at, &ins);
+            let expected: SliceDeque<usize> = (0..at)
+                .chain(cap + 2..3 * cap)
+                .chain(cap..cap + 2)
+                .chain(at..cap - 2)
+                .collect();
+            assert_eq!(deq, expected);
+        }
+
+        let rc = Rc::new(());
+        let mut deq = sdeq![rc.clone(), rc.clone()];
+        deq.insert_slice(1, &[rc.clone(), rc.clone(), rc.clone()]);
+        deq.insert_slice(5, &[]);
+        assert_eq!(Rc::strong_count(&rc), 6);
+        mem::drop(deq);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "use_std")]
     fn vec_into_boxed_slice() {
         let xs = sdeq![1, 2, 3];
         let ys = xs.into_boxed_slice();
         assert_eq!(&*ys, [1, 2, 3]);
     }
-    */
+
+    #[test]
+    #[cfg(feature = "use_std")]
+    fn move_conversions() {
+        use std::collections::VecDeque;
+
+        // Types that are not `Clone`:
+        #[derive(Debug, PartialEq)]
+        struct NoClone(Rc<()>);
+        let rc = Rc::new(());
+        let nc = |n: usize| -> Vec<NoClone> {
+            (0..n).map(|_| NoClone(Rc::clone(&rc))).collect()
+        };
+
+        let deq = SliceDeque::from(nc(3));
+        assert_eq!(deq.len(), 3);
+        assert_eq!(Rc::strong_count(&rc), 4);
+        let v: Vec<_> = deq.into();
+        assert_eq!(v.len(), 3);
+        assert_eq!(Rc::strong_count(&rc), 4);
+
+        let mut vd: VecDeque<_> = nc(4).into();
+        vd.rotate_left(3);
+        vd.push_front(NoClone(Rc::new(())));
+        let deq = SliceDeque::from(vd);
+        assert_eq!(deq.len(), 5);
+        assert_eq!(Rc::strong_count(&deq[0].0), 1);
+        let vd: VecDeque<_> = deq.into();
+        assert_eq!(vd.len(), 5);
+
+        let deq = SliceDeque::from(nc(2).into_boxed_slice());
+        assert_eq!(deq.len(), 2);
+        let b = deq.into_boxed_slice();
+        assert_eq!(b.len(), 2);
+
+        let deq = SliceDeque::from([NoClone(Rc::clone(&rc))]);
+        assert_eq!(deq.len(), 1);
+
+        mem::drop((v, vd, b, deq));
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let deq = SliceDeque::from(VecDeque::from(vec![1, 2, 3]));
+        assert_eq!(deq, [1, 2, 3]);
+        let deq = SliceDeque::<i32>::from([]);
+        assert!(deq.is_empty());
+    }
 
     #[test]
     fn vec_append() {
@@ -4166,6 +7065,113 @@ mod tests {
         assert_eq!(deq2, [5, 6]);
     }
 
+    #[test]
+    fn try_alloc() {
+        let mut deq = SliceDeque::try_with_capacity(4).unwrap();
+        deq.try_extend_from_slice(&[1, 2, 3]).unwrap();
+        deq.try_insert(0, 0).unwrap();
+        deq.try_resize(6, 4).unwrap();
+        assert_eq!(deq, [0, 1, 2, 3, 4, 4]);
+
+        let mut other = SliceDeque::try_from_iter(5..7).unwrap();
+        deq.try_append(&mut other).unwrap();
+        assert!(other.is_empty());
+        assert_eq!(deq, [0, 1, 2, 3, 4, 4, 5, 6]);
+
+        let back = deq.try_split_off(4).unwrap();
+        assert_eq!(back, [4, 4, 5, 6]);
+        assert_eq!(deq.try_clone().unwrap(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn try_alloc_failure() {
+        let huge = isize::MAX as usize / 4;
+        assert!(SliceDeque::<u8>::try_with_capacity(huge).is_err());
+
+        let mut deq = sdeq![1_u8, 2, 3];
+        let ptr = deq.as_ptr();
+        assert!(deq.try_resize(huge, 0).is_err());
+        assert!(deq.try_reserve(huge).is_err());
+        assert_eq!(deq, [1, 2, 3]);
+        assert_eq!(deq.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut deq: SliceDeque<i32> = SliceDeque::new();
+        {
+            let mut c = deq.cursor_front_mut();
+            assert_eq!(c.index(), None);
+            assert_eq!(c.current(), None);
+            c.move_next();
+            assert_eq!(c.index(), None);
+            c.insert_before(1);
+            c.insert_after(0);
+            assert_eq!(c.index(), None);
+            assert_eq!(c.peek_next(), Some(&mut 0));
+            assert_eq!(c.peek_prev(), Some(&mut 1));
+            c.move_next();
+            assert_eq!(c.index(), Some(0));
+            assert_eq!(c.peek_prev(), None);
+            c.move_prev();
+            c.move_prev();
+            assert_eq!(c.index(), Some(1));
+            assert_eq!(c.current(), Some(&mut 1));
+            assert_eq!(c.peek_next(), None);
+        }
+        assert_eq!(deq, [0, 1]);
+
+        deq.extend(2..10);
+        {
+            let mut c = deq.cursor_back_mut();
+            assert_eq!(c.current(), Some(&mut 9));
+            for _ in 0..3 {
+                c.move_prev();
+            }
+            assert_eq!(c.remove_current(), Some(6));
+            assert_eq!(c.index(), Some(6));
+            c.insert_before(60);
+            c.insert_after(70);
+            assert_eq!(c.current(), Some(&mut 7));
+            c.move_prev();
+            c.move_prev();
+            c.move_prev();
+            assert_eq!(c.remove_current(), Some(4));
+            assert_eq!(c.current(), Some(&mut 5));
+        }
+        assert_eq!(deq, [0, 1, 2, 3, 5, 60, 7, 70, 8, 9]);
+
+        {
+            let mut c = deq.cursor_front_mut();
+            for _ in 0..5 {
+                c.move_next();
+            }
+            let back = c.split_after();
+            assert_eq!(back, [7, 70, 8, 9]);
+            c.move_prev();
+            let front = c.split_before();
+            assert_eq!(front, [0, 1, 2, 3]);
+            assert_eq!(c.index(), Some(0));
+            assert_eq!(c.current(), Some(&mut 5));
+        }
+        assert_eq!(deq, [5, 60]);
+
+        {
+            let mut c = deq.cursor_back_mut();
+            c.move_next();
+            assert_eq!(c.index(), None);
+            assert_eq!(c.split_before(), [5, 60]);
+            assert_eq!(c.index(), None);
+            c.insert_before(1);
+            c.move_prev();
+            assert_eq!(c.split_after(), []);
+            c.move_next();
+            assert_eq!(c.split_after(), [1]);
+        }
+        assert!(deq.is_empty());
+    }
+
     #[test]
     fn vec_into_iter_as_slice() {
         let deq = sdeq!['a', 'b', 'c'];
@@ -4295,6 +7301,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn drain_filter_empty() {
         let mut deq: SliceDeque<i32> = sdeq![];
 
@@ -4311,6 +7318,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn drain_filter_zst() {
         let mut deq = sdeq![(), (), (), (), ()];
         let initial_len = deq.len();
@@ -4333,6 +7341,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn drain_filter_false() {
         let mut deq = sdeq![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
 
@@ -4355,6 +7364,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn drain_filter_true() {
         let mut deq = sdeq![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
 
@@ -4378,6 +7388,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn drain_filter_complex() {
         {
             //                [+xxx++++++xxxxx++++x+x++]
@@ -5115,6 +8126,35 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "use_std")]
+    fn eq_ord_coverage() {
+        use std::collections::VecDeque;
+
+        let mut deq = SliceDeque::new();
+        deq.resize(64, 0_u8);
+        assert_eq!(deq, [0_u8; 64]);
+        assert_eq!(deq, &[0_u8; 64]);
+        assert!(deq != [0_u8; 65]);
+        assert_eq!(deq, *vec![0_u8; 64].as_slice());
+        assert_eq!(deq, vec![0_u8; 64].into_boxed_slice());
+
+        let mut vd: VecDeque<_> = (1..6).collect();
+        vd.rotate_left(2);
+        let deq: SliceDeque<_> = vd.iter().cloned().collect();
+        assert_eq!(deq, vd);
+        assert_eq!(deq.partial_cmp(&vd), Some(cmp::Ordering::Equal));
+        vd.push_front(0);
+        assert!(deq != vd);
+        assert!(deq > vd);
+        assert!(deq < [4, 5][..]);
+
+        let mut v = vec![sdeq![2, 1], sdeq![1, 2, 3], sdeq![1, 2]];
+        v.sort();
+        assert_eq!(v, [sdeq![1, 2], sdeq![1, 2, 3], sdeq![2, 1]]);
+        assert_eq!(sdeq![1].cmp(&sdeq![1]), cmp::Ordering::Equal);
+    }
+
     #[test]
     fn vecdeque_ord() {
         let x = SliceDeque::new();
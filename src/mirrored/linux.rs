@@ -1,8 +1,9 @@
 //! Non-racy linux-specific mirrored memory allocation.
+use core::sync::atomic::{AtomicUsize, Ordering};
 use libc::{
     c_char, c_int, c_long, c_uint, c_void, close, ftruncate, mkstemp, mmap,
-    munmap, off_t, size_t, sysconf, unlink, ENOSYS, MAP_FAILED, MAP_FIXED,
-    MAP_SHARED, PROT_READ, PROT_WRITE, _SC_PAGESIZE,
+    munmap, off_t, size_t, sysconf, unlink, ENOMEM, ENOSYS, MAP_FAILED,
+    MAP_FIXED, MAP_SHARED, PROT_READ, PROT_WRITE, _SC_PAGESIZE,
 };
 
 #[cfg(any(target_os = "android", target_os = "openbsd"))]
@@ -47,6 +48,151 @@ fn errno() -> c_int {
     }
 }
 
+/// Number of memory mappings (VMAs) used by each mirrored allocation.
+const MAPPINGS_PER_ALLOCATION: usize = 2;
+
+/// Counts the memory mappings owned by mirrored allocations and enforces an
+/// optional soft limit on them.
+struct MappingCounter {
+    /// Number of live memory mappings.
+    count: AtomicUsize,
+    /// Maximum number of live memory mappings (`usize::MAX` means no
+    /// limit).
+    limit: AtomicUsize,
+}
+
+impl MappingCounter {
+    /// Creates a counter without mappings and without a limit.
+    const fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            limit: AtomicUsize::new(usize::MAX),
+        }
+    }
+
+    /// Reserves `n` mappings.
+    ///
+    /// Fails with `AllocError::MaxMapCount` if that would exceed the limit.
+    fn acquire(&self, n: usize) -> Result<(), AllocError> {
+        let limit = self.limit.load(Ordering::Relaxed);
+        let mut count = self.count.load(Ordering::Relaxed);
+        loop {
+            let new_count = match count.checked_add(n) {
+                Some(c) if c <= limit => c,
+                _ => return Err(AllocError::MaxMapCount),
+            };
+            match self.count.compare_exchange_weak(
+                count,
+                new_count,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(c) => count = c,
+            }
+        }
+    }
+
+    /// Releases `n` mappings.
+    fn release(&self, n: usize) {
+        let prev = self.count.fetch_sub(n, Ordering::Relaxed);
+        debug_assert!(prev >= n);
+    }
+
+    /// Number of live mappings.
+    fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Current limit.
+    fn limit(&self) -> Option<usize> {
+        match self.limit.load(Ordering::Relaxed) {
+            l if l == usize::MAX => None,
+            l => Some(l),
+        }
+    }
+
+    /// Sets the limit.
+    fn set_limit(&self, limit: Option<usize>) {
+        let l = limit.unwrap_or(usize::MAX);
+        self.limit.store(l, Ordering::Relaxed);
+    }
+}
+
+/// Memory mappings owned by all mirrored allocations of the process.
+static MAPPINGS: MappingCounter = MappingCounter::new();
+
+/// Number of memory mappings currently owned by mirrored allocations.
+///
+/// Every non-empty `SliceDeque` owns two memory mappings. Each of them counts
+/// towards the `vm.max_map_count` limit of the process.
+pub fn mapping_count() -> usize {
+    MAPPINGS.count()
+}
+
+/// Soft limit on the number of memory mappings owned by mirrored
+/// allocations, if any.
+pub fn mapping_limit() -> Option<usize> {
+    MAPPINGS.limit()
+}
+
+/// Sets a soft limit on the number of memory mappings owned by mirrored
+/// allocations.
+///
+/// Allocations that would exceed the limit fail with
+/// `AllocError::MaxMapCount` without calling into the kernel. Setting a limit
+/// below `mapping_count()` does not free any memory. `None` removes the limit.
+pub fn set_mapping_limit(limit: Option<usize>) {
+    MAPPINGS.set_limit(limit)
+}
+
+/// Has the process reached its maximum number of memory mappings?
+///
+/// `mmap` fails with `ENOMEM` both when the system is out-of-memory and when
+/// creating `additional` mappings would exceed `vm.max_map_count`. This tells
+/// both apart by counting the mappings in `/proc/self/maps`.
+///
+/// If `/proc` is not available, `ENOMEM` is reported as out-of-memory, as
+/// it would be without `std`.
+#[cfg(feature = "use_std")]
+fn max_map_count_reached(additional: usize) -> bool {
+    max_map_count_reached_in(
+        "/proc/sys/vm/max_map_count",
+        "/proc/self/maps",
+        additional,
+    )
+}
+
+/// Implementation of `max_map_count_reached` reading the limit and the
+/// mappings of the process from the files at `max_path` and `maps_path`.
+#[cfg(feature = "use_std")]
+fn max_map_count_reached_in(
+    max_path: &str, maps_path: &str, additional: usize,
+) -> bool {
+    use std::fs;
+    let max = match fs::read_to_string(max_path)
+        .ok()
+        .and_then(|s| s.trim().parse::<usize>().ok())
+    {
+        Some(max) => max,
+        None => return false,
+    };
+    let count = match fs::read(maps_path) {
+        Ok(maps) => maps.iter().filter(|&&b| b == b'\n').count(),
+        Err(_) => return false,
+    };
+    count + additional > max
+}
+
+/// Has the process reached its maximum number of memory mappings?
+///
+/// Without `std` there is no way to tell, so `ENOMEM` is always reported as
+/// out-of-memory.
+#[cfg(not(feature = "use_std"))]
+fn max_map_count_reached(_additional: usize) -> bool {
+    false
+}
+
 /// Allocates an uninitialzied buffer that holds `size` bytes, where
 /// the bytes in range `[0, size / 2)` are mirrored into the bytes in
 /// range `[size / 2, size)`.
@@ -60,82 +206,106 @@ fn errno() -> c_int {
 ///
 /// This algorithm doesn't have any races.
 ///
+/// Each allocation owns two memory mappings. If these would exceed the soft
+/// limit set with `set_mapping_limit` or the `vm.max_map_count` of the
+/// process, this fails with `AllocError::MaxMapCount`.
+///
 /// # Panics
 ///
 /// If `size` is zero or `size / 2` is not a multiple of the
 /// allocation granularity.
 pub fn allocate_mirrored(size: usize) -> Result<*mut u8, AllocError> {
-    unsafe {
-        let half_size = size / 2;
-        assert!(size != 0);
-        assert!(half_size % allocation_granularity() == 0);
-
-        // create temporary file
-        let mut fname = *b"/tmp/slice_deque_fileXXXXXX\0";
-        let mut fd: c_long =
-            memfd_create(fname.as_mut_ptr() as *mut c_char, 0);
-        if fd == -1 && errno() == ENOSYS {
-            // memfd_create is not implemented, use mkstemp instead:
-            fd = c_long::from(mkstemp(fname.as_mut_ptr() as *mut c_char));
-            // and unlink the file
-            if fd != -1 {
-                unlink(fname.as_mut_ptr() as *mut c_char);
-            }
-        }
-        if fd == -1 {
-            print_error("memfd_create failed");
-            return Err(AllocError::Other);
+    let half_size = size / 2;
+    assert!(size != 0);
+    assert!(half_size % allocation_granularity() == 0);
+
+    MAPPINGS.acquire(MAPPINGS_PER_ALLOCATION)?;
+    let r = unsafe { map_mirrored(size) };
+    if r.is_err() {
+        MAPPINGS.release(MAPPINGS_PER_ALLOCATION);
+    }
+    r
+}
+
+/// Performs the system calls of `allocate_mirrored`.
+unsafe fn map_mirrored(size: usize) -> Result<*mut u8, AllocError> {
+    let half_size = size / 2;
+
+    // create temporary file
+    let mut fname = *b"/tmp/slice_deque_fileXXXXXX\0";
+    let mut fd: c_long = memfd_create(fname.as_mut_ptr() as *mut c_char, 0);
+    if fd == -1 && errno() == ENOSYS {
+        // memfd_create is not implemented, use mkstemp instead:
+        fd = c_long::from(mkstemp(fname.as_mut_ptr() as *mut c_char));
+        // and unlink the file
+        if fd != -1 {
+            unlink(fname.as_mut_ptr() as *mut c_char);
         }
-        let fd = fd as c_int;
-        if ftruncate(fd, half_size as off_t) == -1 {
-            print_error("ftruncate failed");
-            if close(fd) == -1 {
-                print_error("@ftruncate: close failed");
-            }
-            return Err(AllocError::Oom);
-        };
-
-        // mmap memory
-        let ptr = mmap(
-            ptr::null_mut(),
-            size,
-            PROT_READ | PROT_WRITE,
-            MAP_SHARED,
-            fd,
-            0,
-        );
-        if ptr == MAP_FAILED {
-            print_error("@first: mmap failed");
-            if close(fd) == -1 {
-                print_error("@first: close failed");
-            }
-            return Err(AllocError::Oom);
+    }
+    if fd == -1 {
+        print_error("memfd_create failed");
+        return Err(AllocError::Other);
+    }
+    let fd = fd as c_int;
+    if ftruncate(fd, half_size as off_t) == -1 {
+        print_error("ftruncate failed");
+        if close(fd) == -1 {
+            print_error("@ftruncate: close failed");
         }
+        return Err(AllocError::Oom);
+    };
 
-        let ptr2 = mmap(
-            (ptr as *mut u8).offset(half_size as isize) as *mut c_void,
-            half_size,
-            PROT_READ | PROT_WRITE,
-            MAP_SHARED | MAP_FIXED,
-            fd,
-            0,
-        );
-        if ptr2 == MAP_FAILED {
-            print_error("@second: mmap failed");
-            if munmap(ptr, size as size_t) == -1 {
-                print_error("@second: munmap failed");
-            }
-            if close(fd) == -1 {
-                print_error("@second: close failed");
-            }
-            return Err(AllocError::Other);
+    // mmap memory
+    let ptr = mmap(
+        ptr::null_mut(),
+        size,
+        PROT_READ | PROT_WRITE,
+        MAP_SHARED,
+        fd,
+        0,
+    );
+    if ptr == MAP_FAILED {
+        let err = errno();
+        print_error("@first: mmap failed");
+        if close(fd) == -1 {
+            print_error("@first: close failed");
+        }
+        if err == ENOMEM && max_map_count_reached(MAPPINGS_PER_ALLOCATION) {
+            return Err(AllocError::MaxMapCount);
         }
+        return Err(AllocError::Oom);
+    }
 
+    let ptr2 = mmap(
+        (ptr as *mut u8).offset(half_size as isize) as *mut c_void,
+        half_size,
+        PROT_READ | PROT_WRITE,
+        MAP_SHARED | MAP_FIXED,
+        fd,
+        0,
+    );
+    if ptr2 == MAP_FAILED {
+        let err = errno();
+        print_error("@second: mmap failed");
+        if munmap(ptr, size as size_t) == -1 {
+            print_error("@second: munmap failed");
+        }
         if close(fd) == -1 {
-            print_error("@success: close failed");
+            print_error("@second: close failed");
         }
-        Ok(ptr as *mut u8)
+        // The second mapping replaces part of the first one, so it does
+        // not need more memory. It only fails with `ENOMEM` if splitting
+        // the first mapping exceeds `vm.max_map_count`.
+        if err == ENOMEM {
+            return Err(AllocError::MaxMapCount);
+        }
+        return Err(AllocError::Other);
+    }
+
+    if close(fd) == -1 {
+        print_error("@success: close failed");
     }
+    Ok(ptr as *mut u8)
 }
 
 /// Deallocates the mirrored memory region at `ptr` of `size` bytes.
@@ -156,6 +326,7 @@ pub unsafe fn deallocate_mirrored(ptr: *mut u8, size: usize) {
     if munmap(ptr as *mut c_void, size as size_t) == -1 {
         print_error("deallocate munmap failed");
     }
+    MAPPINGS.release(MAPPINGS_PER_ALLOCATION);
 }
 
 /// Prints last os error at `location`.
@@ -171,3 +342,62 @@ fn print_error(location: &str) {
 /// Prints last os error at `location`.
 #[cfg(not(all(debug_assertions, feature = "use_std")))]
 fn print_error(_location: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapping_counter() {
+        let c = MappingCounter::new();
+        assert_eq!(c.count(), 0);
+        assert_eq!(c.limit(), None);
+        c.acquire(2).unwrap();
+        c.acquire(2).unwrap();
+        assert_eq!(c.count(), 4);
+
+        c.set_limit(Some(5));
+        assert_eq!(c.limit(), Some(5));
+        match c.acquire(2) {
+            Err(AllocError::MaxMapCount) => (),
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(c.count(), 4);
+
+        c.release(2);
+        c.acquire(2).unwrap();
+        assert_eq!(c.count(), 4);
+
+        c.set_limit(None);
+        c.acquire(2).unwrap();
+        assert_eq!(c.count(), 6);
+    }
+
+    #[cfg(feature = "use_std")]
+    #[test]
+    fn max_map_count_without_proc() {
+        let missing = "/nonexistent/slice_deque/max_map_count";
+        let maps = "/proc/self/maps";
+        assert!(!max_map_count_reached_in(missing, maps, 2));
+        assert!(!max_map_count_reached_in(missing, missing, usize::MAX / 2));
+
+        // With a readable limit but no mappings file, the error is still
+        // reported as out-of-memory:
+        let dir = std::env::temp_dir();
+        let max = dir.join(format!("slice_deque_max_{}", std::process::id()));
+        std::fs::write(&max, "0\n").unwrap();
+        let max = max.to_str().unwrap();
+        assert!(!max_map_count_reached_in(max, missing, 2));
+        assert!(max_map_count_reached_in(max, maps, 2));
+        std::fs::remove_file(max).unwrap();
+    }
+
+    #[test]
+    fn mapping_count_tracks_allocations() {
+        let size = 2 * allocation_granularity();
+        let ptr = allocate_mirrored(size).unwrap();
+        // Other tests allocate concurrently, so only a lower bound holds:
+        assert!(mapping_count() >= MAPPINGS_PER_ALLOCATION);
+        unsafe { deallocate_mirrored(ptr, size) };
+    }
+}
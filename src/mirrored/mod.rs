@@ -44,6 +44,11 @@ mod linux;
 pub(crate) use self::linux::{
     allocate_mirrored, allocation_granularity, deallocate_mirrored,
 };
+#[cfg(all(
+    any(target_os = "linux", target_os = "android", target_os = "openbsd"),
+    not(feature = "unix_sysv")
+))]
+pub use self::linux::{mapping_count, mapping_limit, set_mapping_limit};
 
 #[cfg(all(
     any(target_os = "macos", target_os = "ios"),
@@ -72,6 +77,10 @@ pub use self::buffer::Buffer;
 use super::*;
 
 /// Allocation error.
+///
+/// New kinds of errors can be added in minor releases, so matches on this
+/// enum need a wildcard arm.
+#[non_exhaustive]
 pub enum AllocError {
     /// The system is Out-of-memory.
     Oom,
@@ -79,6 +88,14 @@ pub enum AllocError {
     ///
     /// Race conditions, exhausted file descriptors, etc.
     Other,
+    /// The process ran out of memory mappings.
+    ///
+    /// Either the `vm.max_map_count` limit of the process or the soft limit
+    /// set with `set_mapping_limit` was reached.
+    MaxMapCount,
+    /// The capacity would exceed the maximum capacity of the deque's
+    /// `GrowthPolicy`.
+    MaxCapacity,
 }
 
 impl crate::fmt::Debug for AllocError {
@@ -86,6 +103,12 @@ impl crate::fmt::Debug for AllocError {
         match self {
             AllocError::Oom => write!(f, "out-of-memory"),
             AllocError::Other => write!(f, "other (not out-of-memory)"),
+            AllocError::MaxMapCount => {
+                write!(f, "maximum number of memory mappings reached")
+            }
+            AllocError::MaxCapacity => {
+                write!(f, "maximum capacity of the growth policy exceeded")
+            }
         }
     }
 }
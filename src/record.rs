@@ -0,0 +1,271 @@
+//! A queue of variable-length byte records.
+
+use super::*;
+
+/// Size of the length prefix of a record.
+const PREFIX: usize = 4;
+
+/// Reads the length prefix at the beginning of `bytes`.
+#[inline]
+fn record_len(bytes: &[u8]) -> usize {
+    let mut prefix = [0_u8; PREFIX];
+    prefix.copy_from_slice(&bytes[..PREFIX]);
+    u32::from_le_bytes(prefix) as usize
+}
+
+/// A queue of variable-length byte records.
+///
+/// Records are stored back-to-back in a single `SliceDeque<u8>`, each one
+/// preceded by its length as a 4-byte little-endian integer, so pushing a
+/// record does not allocate unless the deque needs to grow. Since the
+/// buffer is mirrored, every record is contiguous in memory, even when it
+/// wraps around the end of the ring buffer.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::RecordDeque;
+/// let mut q = RecordDeque::new();
+/// q.push_record(b"hello");
+/// q.push_record(b"");
+/// q.push_record(b"world");
+/// assert_eq!(q.len(), 3);
+/// assert_eq!(q.front_record(), Some(&b"hello"[..]));
+/// assert_eq!(q.pop_record(), Some(&b"hello"[..]));
+/// let rest: Vec<&[u8]> = q.iter().collect();
+/// assert_eq!(rest, [&b""[..], &b"world"[..]]);
+/// ```
+#[derive(Clone, Default)]
+pub struct RecordDeque {
+    /// Length-prefixed records.
+    bytes: SliceDeque<u8>,
+    /// Number of records.
+    len: usize,
+}
+
+impl RecordDeque {
+    /// Creates a new empty queue.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            bytes: SliceDeque::new(),
+            len: 0,
+        }
+    }
+
+    /// Creates a new empty queue with capacity for at least `n` bytes,
+    /// including 4 bytes of overhead per record.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            bytes: SliceDeque::with_capacity(n),
+            len: 0,
+        }
+    }
+
+    /// Number of records in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the queue empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of bytes used by the records, including their length
+    /// prefixes.
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Number of bytes that the queue can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    /// Appends `record` to the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// If `record` is longer than `u32::MAX` bytes, if the new capacity
+    /// overflows `usize`, or on OOM.
+    #[inline]
+    pub fn push_record(&mut self, record: &[u8]) {
+        assert!(
+            record.len() <= u32::MAX as usize,
+            "record of {} bytes is too long",
+            record.len()
+        );
+        self.bytes.reserve(PREFIX + record.len());
+        self.bytes
+            .extend_from_slice(&(record.len() as u32).to_le_bytes());
+        self.bytes.extend_from_slice(record);
+        self.len += 1;
+    }
+
+    /// Returns the first record, or `None` if the queue is empty.
+    #[inline]
+    pub fn front_record(&self) -> Option<&[u8]> {
+        self.iter().next()
+    }
+
+    /// Returns the first record mutably, or `None` if the queue is empty.
+    #[inline]
+    pub fn front_record_mut(&mut self) -> Option<&mut [u8]> {
+        if self.is_empty() {
+            return None;
+        }
+        let len = record_len(&self.bytes);
+        Some(&mut self.bytes[PREFIX..PREFIX + len])
+    }
+
+    /// Removes the first record and returns it, or `None` if the queue is
+    /// empty.
+    ///
+    /// The returned slice points to the memory that the record occupied in
+    /// the queue, which is not reused until the queue is modified again, so
+    /// popping does not copy the record.
+    #[inline]
+    pub fn pop_record(&mut self) -> Option<&[u8]> {
+        if self.is_empty() {
+            return None;
+        }
+        let len = record_len(&self.bytes);
+        self.len -= 1;
+        unsafe {
+            let ptr = self.bytes.as_ptr().add(PREFIX);
+            // The bytes do not need to be dropped, and moving the head
+            // neither reallocates nor overwrites the memory of the record:
+            self.bytes.move_head_unchecked((PREFIX + len) as isize);
+            Some(slice::from_raw_parts(ptr, len))
+        }
+    }
+
+    /// Iterates over the records from the front to the back.
+    #[inline]
+    pub fn iter(&self) -> Records<'_> {
+        Records {
+            bytes: &self.bytes,
+            len: self.len,
+        }
+    }
+
+    /// Removes all records.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.len = 0;
+    }
+}
+
+impl fmt::Debug for RecordDeque {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a RecordDeque {
+    type Item = &'a [u8];
+    type IntoIter = Records<'a>;
+    #[inline]
+    fn into_iter(self) -> Records<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> Extend<&'a [u8]> for RecordDeque {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+        for record in iter {
+            self.push_record(record);
+        }
+    }
+}
+
+/// An iterator over the records of a [`RecordDeque`].
+///
+/// [`RecordDeque`]: struct.RecordDeque.html
+#[derive(Clone, Debug)]
+pub struct Records<'a> {
+    /// Remaining length-prefixed records.
+    bytes: &'a [u8],
+    /// Number of remaining records.
+    len: usize,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.len == 0 {
+            return None;
+        }
+        let len = record_len(self.bytes);
+        let (record, rest) = self.bytes[PREFIX..].split_at(len);
+        self.bytes = rest;
+        self.len -= 1;
+        Some(record)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a> ExactSizeIterator for Records<'a> {}
+
+impl<'a> iter::FusedIterator for Records<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_wrap_around() {
+        let mut q = RecordDeque::with_capacity(1);
+        let cap = q.capacity();
+        let mut expected = std::collections::VecDeque::new();
+        let mut n = 0_usize;
+        // Keep the queue about half full while the records wrap around the
+        // buffer several times:
+        while n < cap * 4 {
+            let size = n % 97;
+            let record: Vec<u8> = (0..size).map(|i| (i + n) as u8).collect();
+            q.push_record(&record);
+            expected.push_back(record);
+            n += size + PREFIX;
+            while q.byte_len() > cap / 2 {
+                let popped = q.pop_record().map(<[u8]>::to_vec);
+                assert_eq!(popped, expected.pop_front());
+            }
+            assert_eq!(q.len(), expected.len());
+        }
+        assert_eq!(q.capacity(), cap);
+        assert!(q.iter().eq(expected.iter().map(|r| &r[..])));
+        assert_eq!(q.iter().len(), q.len());
+    }
+
+    #[test]
+    fn front_record_mut() {
+        let mut q = RecordDeque::new();
+        assert!(q.front_record_mut().is_none());
+        assert!(q.pop_record().is_none());
+        q.extend(vec![&b"ab"[..], &b"cd"[..]]);
+        q.front_record_mut().unwrap()[0] = b'x';
+        assert_eq!(format!("{:?}", q), "[[120, 98], [99, 100]]");
+        q.clear();
+        assert!(q.is_empty());
+        assert_eq!(q.byte_len(), 0);
+    }
+}
@@ -0,0 +1,547 @@
+//! A queue of fixed-width rows.
+
+use super::*;
+
+/// A queue of rows of `width` elements each.
+///
+/// Rows are stored back-to-back in a single `SliceDeque<T>`. Since the
+/// buffer is mirrored, any range of rows is a contiguous slice, even when it
+/// wraps around the end of the ring buffer. This fits, e.g., image filters
+/// that keep the last `k` scanlines of an image, or interleaved audio where
+/// each row is a frame holding one sample per channel.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::RowDeque;
+/// // Keep the last 2 scanlines of an image 3 pixels wide:
+/// let mut lines = RowDeque::new(3);
+/// for line in &[[1, 2, 3], [4, 5, 6], [7, 8, 9]] {
+///     lines.push_row(line);
+///     lines.truncate_front_rows(2);
+/// }
+/// assert_eq!(lines.rows(..), &[4, 5, 6, 7, 8, 9]);
+/// let view = lines.rows_view(..);
+/// assert_eq!(view.get(1, 2), Some(&9));
+/// assert!(view.column(1).eq(&[5, 8]));
+/// ```
+pub struct RowDeque<T> {
+    /// Elements of the rows.
+    deq: SliceDeque<T>,
+    /// Number of elements per row.
+    width: usize,
+}
+
+impl<T> RowDeque<T> {
+    /// Creates a new empty queue of rows of `width` elements.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is zero.
+    #[inline]
+    pub fn new(width: usize) -> Self {
+        Self::with_capacity(width, 0)
+    }
+
+    /// Creates a new empty queue of rows of `width` elements with capacity
+    /// for at least `rows` rows.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is zero, if the capacity overflows `usize`, or on OOM.
+    #[inline]
+    pub fn with_capacity(width: usize, rows: usize) -> Self {
+        assert!(width > 0, "rows must have at least one element");
+        let cap = width.checked_mul(rows).expect("capacity overflow");
+        Self {
+            deq: SliceDeque::with_capacity(cap),
+            width,
+        }
+    }
+
+    /// Number of elements per row.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.deq.len() / self.width
+    }
+
+    /// Is the queue empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.deq.is_empty()
+    }
+
+    /// Number of rows that the queue can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.deq.capacity() / self.width
+    }
+
+    /// Elements of all rows, from the first element of the first row to the
+    /// last element of the last row.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.deq.as_slice()
+    }
+
+    /// Elements of all rows, from the first element of the first row to the
+    /// last element of the last row.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.deq.as_mut_slice()
+    }
+
+    /// Returns the row at index `i`, or `None` if it is out of bounds.
+    #[inline]
+    pub fn row(&self, i: usize) -> Option<&[T]> {
+        if i >= self.len() {
+            return None;
+        }
+        Some(&self.deq[i * self.width..(i + 1) * self.width])
+    }
+
+    /// Returns the row at index `i` mutably, or `None` if it is out of
+    /// bounds.
+    #[inline]
+    pub fn row_mut(&mut self, i: usize) -> Option<&mut [T]> {
+        if i >= self.len() {
+            return None;
+        }
+        let w = self.width;
+        Some(&mut self.deq[i * w..(i + 1) * w])
+    }
+
+    /// Returns the elements of the rows in `range` as a contiguous slice.
+    ///
+    /// # Panics
+    ///
+    /// If the starting point is greater than the end point or if the end
+    /// point is greater than the number of rows.
+    #[inline]
+    pub fn rows<R>(&self, range: R) -> &[T]
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let (start, end) = self.row_bounds(range);
+        &self.deq[start..end]
+    }
+
+    /// Returns the elements of the rows in `range` as a contiguous mutable
+    /// slice.
+    ///
+    /// # Panics
+    ///
+    /// If the starting point is greater than the end point or if the end
+    /// point is greater than the number of rows.
+    #[inline]
+    pub fn rows_mut<R>(&mut self, range: R) -> &mut [T]
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let (start, end) = self.row_bounds(range);
+        &mut self.deq[start..end]
+    }
+
+    /// Returns a two-dimensional view of the rows in `range`.
+    ///
+    /// # Panics
+    ///
+    /// If the starting point is greater than the end point or if the end
+    /// point is greater than the number of rows.
+    #[inline]
+    pub fn rows_view<R>(&self, range: R) -> RowsView<'_, T>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        RowsView {
+            data: self.rows(range),
+            width: self.width,
+        }
+    }
+
+    /// Iterates over the rows from the front to the back.
+    #[inline]
+    pub fn iter_rows(&self) -> slice::ChunksExact<'_, T> {
+        self.deq.chunks_exact(self.width)
+    }
+
+    /// Removes the first row and returns its elements, or returns `None` if
+    /// the queue is empty.
+    #[inline]
+    pub fn pop_row(&mut self) -> Option<Drain<'_, T>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.deq.drain(..self.width))
+    }
+
+    /// Removes the last row and returns its elements, or returns `None` if
+    /// the queue is empty.
+    #[inline]
+    pub fn pop_back_row(&mut self) -> Option<Drain<'_, T>> {
+        if self.is_empty() {
+            return None;
+        }
+        let start = self.deq.len() - self.width;
+        Some(self.deq.drain(start..))
+    }
+
+    /// Shortens the queue by removing rows from the front, keeping the last
+    /// `rows` rows.
+    ///
+    /// If `rows` is greater than the number of rows, this has no effect.
+    #[inline]
+    pub fn truncate_front_rows(&mut self, rows: usize) {
+        if rows < self.len() {
+            self.deq.truncate_front(rows * self.width);
+        }
+    }
+
+    /// Shortens the queue by removing rows from the back, keeping the first
+    /// `rows` rows.
+    ///
+    /// If `rows` is greater than the number of rows, this has no effect.
+    #[inline]
+    pub fn truncate_back_rows(&mut self, rows: usize) {
+        if rows < self.len() {
+            self.deq.truncate(rows * self.width);
+        }
+    }
+
+    /// Removes all rows.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.deq.clear();
+    }
+
+    /// Returns the elements of all rows.
+    #[inline]
+    pub fn into_inner(self) -> SliceDeque<T> {
+        self.deq
+    }
+
+    /// Converts a range of rows into a range of elements.
+    ///
+    /// # Panics
+    ///
+    /// If the starting point is greater than the end point or if the end
+    /// point is greater than the number of rows.
+    #[inline]
+    fn row_bounds<R>(&self, range: R) -> (usize, usize)
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        use ops::Bound::{Excluded, Included, Unbounded};
+        let start = match range.start_bound() {
+            Included(&n) => n,
+            Excluded(&n) => n + 1,
+            Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Included(&n) => n + 1,
+            Excluded(&n) => n,
+            Unbounded => self.len(),
+        };
+        assert!(
+            start <= end,
+            "row range starts at {} but ends at {}",
+            start,
+            end
+        );
+        assert!(
+            end <= self.len(),
+            "row range end {} out of range for {} rows",
+            end,
+            self.len()
+        );
+        (start * self.width, end * self.width)
+    }
+}
+
+impl<T: Clone> RowDeque<T> {
+    /// Appends `row` to the back of the queue.
+    ///
+    /// If cloning an element of `row` panics, the queue is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `row` is not `width()`, if the new capacity
+    /// overflows `usize`, or on OOM.
+    #[inline]
+    pub fn push_row(&mut self, row: &[T]) {
+        self.check_width(row);
+        self.deq.reserve(self.width);
+        clone_into(&mut self.deq.spare_back_mut()[..self.width], row);
+        unsafe { self.deq.commit_back(self.width) };
+    }
+
+    /// Prepends `row` to the front of the queue.
+    ///
+    /// If cloning an element of `row` panics, the queue is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `row` is not `width()`, if the new capacity
+    /// overflows `usize`, or on OOM.
+    #[inline]
+    pub fn push_front_row(&mut self, row: &[T]) {
+        self.check_width(row);
+        self.deq.reserve(self.width);
+        let spare = self.deq.spare_front_mut();
+        let start = spare.len() - self.width;
+        clone_into(&mut spare[start..], row);
+        unsafe { self.deq.commit_front(self.width) };
+    }
+
+    /// Panics if the length of `row` is not `width()`.
+    #[inline]
+    fn check_width(&self, row: &[T]) {
+        assert_eq!(
+            row.len(),
+            self.width,
+            "row of {} elements pushed into rows of {} elements",
+            row.len(),
+            self.width
+        );
+    }
+}
+
+/// Clones the elements of `src` into `dst`, which has the same length.
+///
+/// If a clone panics, the elements already cloned are dropped, so that the
+/// caller only needs to add `dst` to the deque once all of it is written.
+fn clone_into<T: Clone>(dst: &mut [mem::MaybeUninit<T>], src: &[T]) {
+    /// Drops the first `len` elements of `dst` unless forgotten.
+    struct Staged<'a, T: 'a> {
+        /// Memory being written to.
+        dst: &'a mut [mem::MaybeUninit<T>],
+        /// Number of elements written.
+        len: usize,
+    }
+
+    impl<'a, T: 'a> Drop for Staged<'a, T> {
+        fn drop(&mut self) {
+            for v in &mut self.dst[..self.len] {
+                unsafe { ptr::drop_in_place(v.as_mut_ptr()) };
+            }
+        }
+    }
+
+    debug_assert_eq!(dst.len(), src.len());
+    let mut staged = Staged { dst, len: 0 };
+    for v in src {
+        staged.dst[staged.len] = mem::MaybeUninit::new(v.clone());
+        staged.len += 1;
+    }
+    mem::forget(staged);
+}
+
+impl<T: Clone> Clone for RowDeque<T> {
+    fn clone(&self) -> Self {
+        Self {
+            deq: self.deq.clone(),
+            width: self.width,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RowDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.iter_rows()).finish()
+    }
+}
+
+/// A two-dimensional view of consecutive rows of a [`RowDeque`].
+///
+/// The view is backed by a contiguous slice in which element `(r, c)` is at
+/// index `r * width() + c`, so columns are strided by `width()`.
+///
+/// [`RowDeque`]: struct.RowDeque.html
+pub struct RowsView<'a, T> {
+    /// Elements of the rows.
+    data: &'a [T],
+    /// Number of elements per row.
+    width: usize,
+}
+
+impl<'a, T> RowsView<'a, T> {
+    /// Number of elements per row.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows in the view.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.data.len() / self.width
+    }
+
+    /// Elements of the rows in the view as a contiguous slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [T] {
+        self.data
+    }
+
+    /// Returns row `r`, or `None` if it is out of bounds.
+    #[inline]
+    pub fn row(&self, r: usize) -> Option<&'a [T]> {
+        if r >= self.height() {
+            return None;
+        }
+        Some(&self.data[r * self.width..(r + 1) * self.width])
+    }
+
+    /// Returns the element at row `r` and column `c`, or `None` if it is out
+    /// of bounds.
+    #[inline]
+    pub fn get(&self, r: usize, c: usize) -> Option<&'a T> {
+        if c >= self.width {
+            return None;
+        }
+        self.data.get(r * self.width + c)
+    }
+
+    /// Iterates over the elements of column `c`, from the first row to the
+    /// last one.
+    ///
+    /// # Panics
+    ///
+    /// If `c` is not less than `width()`.
+    #[inline]
+    pub fn column(&self, c: usize) -> iter::StepBy<slice::Iter<'a, T>> {
+        assert!(
+            c < self.width,
+            "column {} out of range for rows of {} elements",
+            c,
+            self.width
+        );
+        self.data[c..].iter().step_by(self.width)
+    }
+
+    /// Iterates over the rows of the view.
+    #[inline]
+    pub fn iter(&self) -> slice::ChunksExact<'a, T> {
+        self.data.chunks_exact(self.width)
+    }
+}
+
+impl<'a, T> Clone for RowsView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for RowsView<'a, T> {}
+
+impl<'a, T: fmt::Debug> fmt::Debug for RowsView<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_wrap_around() {
+        let mut q = RowDeque::with_capacity(3, 1);
+        let cap = q.capacity();
+        assert!(cap >= 1);
+        for i in 0..cap * 5 {
+            let row = [i, i + 1, i + 2];
+            q.push_row(&row);
+            q.truncate_front_rows(cap / 2 + 1);
+            assert_eq!(q.row(q.len() - 1), Some(&row[..]));
+        }
+        assert_eq!(q.capacity(), cap);
+        let first = q.row(0).unwrap()[0];
+        let all = q.rows(..);
+        assert_eq!(all.len(), q.len() * 3);
+        for (r, row) in q.iter_rows().enumerate() {
+            assert_eq!(row, &[first + r, first + r + 1, first + r + 2]);
+        }
+        let view = q.rows_view(1..);
+        assert_eq!(view.height(), q.len() - 1);
+        assert!(view
+            .column(2)
+            .cloned()
+            .eq((first + 3..).take(view.height())));
+        assert_eq!(view.get(0, 3), None);
+        assert_eq!(view.get(view.height(), 0), None);
+    }
+
+    #[test]
+    fn push_pop_rows() {
+        let mut q = RowDeque::new(2);
+        assert!(q.pop_row().is_none());
+        q.push_row(&[String::from("a"), String::from("b")]);
+        q.push_row(&[String::from("c"), String::from("d")]);
+        q.push_front_row(&[String::from("y"), String::from("z")]);
+        assert_eq!(q.len(), 3);
+        assert_eq!(q.rows(1..=1), &["a", "b"]);
+        assert!(q.pop_row().unwrap().eq(vec!["y", "z"]));
+        assert!(q.pop_back_row().unwrap().eq(vec!["c", "d"]));
+        q.rows_mut(..)[1].push('!');
+        assert_eq!(format!("{:?}", q), r#"[["a", "b!"]]"#);
+        q.truncate_back_rows(0);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn push_row_panic_keeps_whole_rows() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        /// Panics when cloned while the shared counter is zero.
+        #[derive(Debug)]
+        struct Fuse(Rc<Cell<usize>>);
+        impl Clone for Fuse {
+            fn clone(&self) -> Self {
+                let n = self.0.get();
+                assert!(n > 0, "fuse blown");
+                self.0.set(n - 1);
+                Fuse(Rc::clone(&self.0))
+            }
+        }
+
+        let fuse = Rc::new(Cell::new(usize::MAX));
+        let row: Vec<_> = (0..3).map(|_| Fuse(Rc::clone(&fuse))).collect();
+        let mut q = RowDeque::new(3);
+        q.push_row(&row);
+        for push_front in &[false, true] {
+            fuse.set(2);
+            let r =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    if *push_front {
+                        q.push_front_row(&row);
+                    } else {
+                        q.push_row(&row);
+                    }
+                }));
+            assert!(r.is_err());
+            assert_eq!(q.len(), 1);
+            assert_eq!(q.deq.len(), 3);
+            // The two clones made before the panic were dropped:
+            assert_eq!(Rc::strong_count(&fuse), 7);
+        }
+        fuse.set(usize::MAX);
+        q.push_front_row(&row);
+        assert_eq!(q.len(), 2);
+        assert_eq!(Rc::strong_count(&fuse), 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_row_of_wrong_width() {
+        let mut q = RowDeque::new(4);
+        q.push_row(&[1, 2, 3]);
+    }
+}
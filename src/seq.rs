@@ -0,0 +1,453 @@
+//! A double-ended queue indexed by absolute sequence numbers.
+
+use super::*;
+
+/// A queue whose elements are addressed by a monotonically increasing
+/// sequence number.
+///
+/// Every element pushed at the back is assigned the next sequence number.
+/// Popping elements from the front advances the sequence number of the front
+/// element, so that the sequence number of the remaining elements does not
+/// change. This is the bookkeeping needed by retransmission and replay
+/// buffers, where elements are acknowledged by sequence number.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::SeqDeque;
+/// let mut deq = SeqDeque::with_start_seq(100);
+/// for c in b"hello" {
+///     deq.push_back(*c);
+/// }
+/// assert_eq!(deq.get_seq(101), Some(&b'e'));
+///
+/// // Acknowledge everything before 103:
+/// assert_eq!(deq.pop_front_until(103).count(), 3);
+/// assert_eq!(deq.front_seq(), 103);
+/// assert_eq!(deq.get_seq(101), None);
+/// assert_eq!(deq.range_seq(103..105), b"lo");
+/// ```
+pub struct SeqDeque<T> {
+    /// Elements in the queue.
+    deq: SliceDeque<T>,
+    /// Sequence number of the front element.
+    front_seq: u64,
+}
+
+impl<T> SeqDeque<T> {
+    /// Creates a new empty deque whose first element will have sequence
+    /// number `0`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_start_seq(0)
+    }
+
+    /// Creates a new empty deque whose first element will have sequence
+    /// number `seq`.
+    #[inline]
+    pub fn with_start_seq(seq: u64) -> Self {
+        Self {
+            deq: SliceDeque::new(),
+            front_seq: seq,
+        }
+    }
+
+    /// Creates a new empty deque with space for at least `n` elements whose
+    /// first element will have sequence number `seq`.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn with_capacity(n: usize, seq: u64) -> Self {
+        Self {
+            deq: SliceDeque::with_capacity(n),
+            front_seq: seq,
+        }
+    }
+
+    /// Sequence number of the front element.
+    ///
+    /// If the deque is empty, this is the sequence number that the next
+    /// element pushed will have.
+    #[inline]
+    pub fn front_seq(&self) -> u64 {
+        self.front_seq
+    }
+
+    /// Sequence number that the next element pushed at the back will have.
+    #[inline]
+    pub fn end_seq(&self) -> u64 {
+        self.front_seq + self.deq.len() as u64
+    }
+
+    /// Number of elements in the deque.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.deq.len()
+    }
+
+    /// Is the deque empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.deq.is_empty()
+    }
+
+    /// Number of elements the deque can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.deq.capacity()
+    }
+
+    /// Reserves capacity for inserting at least `additional` elements
+    /// without reallocating.
+    ///
+    /// # Panics
+    ///
+    /// If the new capacity overflows `usize` or on OOM.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.deq.reserve(additional);
+    }
+
+    /// Extracts a slice containing the entire deque.
+    ///
+    /// The element at index `i` has sequence number `front_seq() + i`.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.deq.as_slice()
+    }
+
+    /// Extracts a mutable slice containing the entire deque.
+    ///
+    /// The element at index `i` has sequence number `front_seq() + i`.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.deq.as_mut_slice()
+    }
+
+    /// Appends `value` to the deque and returns its sequence number.
+    ///
+    /// # Panics
+    ///
+    /// If the new capacity overflows `usize` or on OOM.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::SeqDeque;
+    /// let mut deq = SeqDeque::with_start_seq(7);
+    /// assert_eq!(deq.push_back('a'), 7);
+    /// assert_eq!(deq.push_back('b'), 8);
+    /// assert_eq!(deq.end_seq(), 9);
+    /// ```
+    #[inline]
+    pub fn push_back(&mut self, value: T) -> u64 {
+        let seq = self.end_seq();
+        self.deq.push_back(value);
+        seq
+    }
+
+    /// Removes the front element and returns it together with its sequence
+    /// number, or `None` if the deque is empty.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<(u64, T)> {
+        let value = self.deq.pop_front()?;
+        let seq = self.front_seq;
+        self.front_seq += 1;
+        Some((seq, value))
+    }
+
+    /// Removes the back element and returns it together with its sequence
+    /// number, or `None` if the deque is empty.
+    ///
+    /// The sequence number of the removed element is assigned again to the
+    /// next element pushed.
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<(u64, T)> {
+        let value = self.deq.pop_back()?;
+        Some((self.end_seq(), value))
+    }
+
+    /// Index of the element with sequence number `seq`, if it is in the
+    /// deque.
+    #[inline]
+    fn index_of(&self, seq: u64) -> Option<usize> {
+        if seq < self.front_seq || seq >= self.end_seq() {
+            return None;
+        }
+        Some((seq - self.front_seq) as usize)
+    }
+
+    /// Index into the deque of sequence number `seq`, clamped to
+    /// `[0, len]`.
+    #[inline]
+    fn clamped_index_of(&self, seq: u64) -> usize {
+        if seq <= self.front_seq {
+            0
+        } else if seq >= self.end_seq() {
+            self.len()
+        } else {
+            (seq - self.front_seq) as usize
+        }
+    }
+
+    /// Returns a reference to the element with sequence number `seq`, or
+    /// `None` if it is not in the deque.
+    #[inline]
+    pub fn get_seq(&self, seq: u64) -> Option<&T> {
+        let idx = self.index_of(seq)?;
+        self.deq.get(idx)
+    }
+
+    /// Returns a mutable reference to the element with sequence number
+    /// `seq`, or `None` if it is not in the deque.
+    #[inline]
+    pub fn get_seq_mut(&mut self, seq: u64) -> Option<&mut T> {
+        let idx = self.index_of(seq)?;
+        self.deq.get_mut(idx)
+    }
+
+    /// Returns the elements with sequence numbers in `range`.
+    ///
+    /// # Panics
+    ///
+    /// If the starting point is greater than the end point, or if the range
+    /// is not within `front_seq()..end_seq()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::SeqDeque;
+    /// let mut deq = SeqDeque::with_start_seq(10);
+    /// for i in 0..5 {
+    ///     deq.push_back(i);
+    /// }
+    /// assert_eq!(deq.range_seq(11..13), &[1, 2]);
+    /// assert_eq!(deq.range_seq(13..), &[3, 4]);
+    /// assert_eq!(deq.range_seq(..=10), &[0]);
+    /// ```
+    #[inline]
+    pub fn range_seq<R>(&self, range: R) -> &[T]
+    where
+        R: ops::RangeBounds<u64>,
+    {
+        let (start, end) = self.seq_bounds(range);
+        &self.as_slice()[start..end]
+    }
+
+    /// Returns the elements with sequence numbers in `range` mutably.
+    ///
+    /// # Panics
+    ///
+    /// If the starting point is greater than the end point, or if the range
+    /// is not within `front_seq()..end_seq()`.
+    #[inline]
+    pub fn range_seq_mut<R>(&mut self, range: R) -> &mut [T]
+    where
+        R: ops::RangeBounds<u64>,
+    {
+        let (start, end) = self.seq_bounds(range);
+        &mut self.as_mut_slice()[start..end]
+    }
+
+    /// Converts a range of sequence numbers into a range of indices.
+    ///
+    /// # Panics
+    ///
+    /// If the starting point is greater than the end point, or if the range
+    /// is not within `front_seq()..end_seq()`.
+    #[inline]
+    fn seq_bounds<R>(&self, range: R) -> (usize, usize)
+    where
+        R: ops::RangeBounds<u64>,
+    {
+        use ops::Bound::{Excluded, Included, Unbounded};
+        let start = match range.start_bound() {
+            Included(&n) => n,
+            Excluded(&n) => n + 1,
+            Unbounded => self.front_seq,
+        };
+        let end = match range.end_bound() {
+            Included(&n) => n + 1,
+            Excluded(&n) => n,
+            Unbounded => self.end_seq(),
+        };
+        assert!(
+            start <= end,
+            "sequence range starts at {} but ends at {}",
+            start,
+            end
+        );
+        assert!(
+            start >= self.front_seq && end <= self.end_seq(),
+            "sequence range {}..{} out of bounds {}..{}",
+            start,
+            end,
+            self.front_seq,
+            self.end_seq()
+        );
+        (
+            (start - self.front_seq) as usize,
+            (end - self.front_seq) as usize,
+        )
+    }
+
+    /// Removes all elements with a sequence number lower than `seq` and
+    /// returns them in an iterator.
+    ///
+    /// If `seq` is greater than `end_seq()` all elements are removed and the
+    /// front sequence number becomes `end_seq()`, that is, the sequence
+    /// numbers of the elements pushed afterwards are not affected.
+    ///
+    /// Note: the elements are removed even if the iterator is not consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::SeqDeque;
+    /// let mut deq = SeqDeque::new();
+    /// for i in 0..5 {
+    ///     deq.push_back(i * 10);
+    /// }
+    /// let acked: Vec<_> = deq.pop_front_until(2).collect();
+    /// assert_eq!(acked, [0, 10]);
+    /// assert_eq!(deq.front_seq(), 2);
+    /// // Already acknowledged:
+    /// assert_eq!(deq.pop_front_until(1).count(), 0);
+    /// assert_eq!(deq.as_slice(), &[20, 30, 40]);
+    /// ```
+    #[inline]
+    pub fn pop_front_until(&mut self, seq: u64) -> Drain<'_, T> {
+        let n = self.clamped_index_of(seq);
+        self.front_seq += n as u64;
+        self.deq.drain(..n)
+    }
+
+    /// Iterates over the elements together with their sequence numbers.
+    #[inline]
+    pub fn iter_seq(&self) -> iter::Zip<ops::Range<u64>, slice::Iter<'_, T>> {
+        (self.front_seq..self.end_seq()).zip(self.deq.iter())
+    }
+
+    /// Removes all elements from the deque.
+    ///
+    /// The front sequence number becomes `end_seq()`.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.front_seq = self.end_seq();
+        self.deq.clear();
+    }
+
+    /// Converts the deque into the underlying `SliceDeque`, discarding the
+    /// sequence numbers.
+    #[inline]
+    pub fn into_inner(self) -> SliceDeque<T> {
+        self.deq
+    }
+}
+
+impl<T> Default for SeqDeque<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SeqDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_map().entries(self.iter_seq()).finish()
+    }
+}
+
+impl<T: Clone> Clone for SeqDeque<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            deq: self.deq.clone(),
+            front_seq: self.front_seq,
+        }
+    }
+}
+
+impl<T> Extend<T> for SeqDeque<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.deq.extend(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn seq_survives_pops() {
+        let mut deq = SeqDeque::with_start_seq(5);
+        assert_eq!(deq.pop_front(), None);
+        for i in 0..10 {
+            assert_eq!(deq.push_back(i), 5 + i as u64);
+        }
+        assert_eq!(deq.pop_front(), Some((5, 0)));
+        assert_eq!(deq.pop_back(), Some((14, 9)));
+        assert_eq!(deq.push_back(42), 14);
+        assert_eq!(deq.get_seq(5), None);
+        assert_eq!(deq.get_seq(6), Some(&1));
+        assert_eq!(deq.get_seq(14), Some(&42));
+        assert_eq!(deq.get_seq(15), None);
+        *deq.get_seq_mut(7).unwrap() = 20;
+        assert_eq!(deq.range_seq(6..9), &[1, 20, 3]);
+        assert_eq!(deq.range_seq(..), deq.as_slice());
+        assert_eq!(deq.range_seq(14..15), &[42]);
+        assert_eq!(deq.range_seq(15..15), &[] as &[i32]);
+        deq.range_seq_mut(6..=7).copy_from_slice(&[0, 0]);
+        assert_eq!(deq.range_seq(..8), &[0, 0]);
+
+        assert_eq!(deq.pop_front_until(9).collect::<Vec<_>>(), [0, 0, 3]);
+        assert_eq!(deq.front_seq(), 9);
+        assert_eq!(deq.pop_front_until(3).count(), 0);
+        assert_eq!(deq.front_seq(), 9);
+        let pairs: Vec<_> = deq.iter_seq().map(|(s, &v)| (s, v)).collect();
+        assert_eq!(
+            pairs,
+            [(9, 4), (10, 5), (11, 6), (12, 7), (13, 8), (14, 42)]
+        );
+
+        assert_eq!(deq.pop_front_until(100).count(), 6);
+        assert!(deq.is_empty());
+        assert_eq!(deq.front_seq(), 15);
+        assert_eq!(deq.push_back(1), 15);
+        deq.clear();
+        assert_eq!(deq.end_seq(), 16);
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_seq_before_front() {
+        let mut deq = SeqDeque::new();
+        deq.extend(0..4);
+        deq.pop_front_until(2);
+        let _ = deq.range_seq(1..3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_seq_past_end() {
+        let mut deq = SeqDeque::new();
+        deq.extend(0..4);
+        let _ = deq.range_seq(2..5);
+    }
+
+    #[test]
+    fn pop_front_until_drops() {
+        let counter = Rc::new(());
+        let mut deq = SeqDeque::new();
+        for _ in 0..4 {
+            deq.push_back(Rc::clone(&counter));
+        }
+        deq.pop_front_until(3);
+        assert_eq!(Rc::strong_count(&counter), 2);
+        assert_eq!(deq.front_seq(), 3);
+    }
+}
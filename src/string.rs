@@ -0,0 +1,622 @@
+//! A double-ended queue of UTF-8 text that derefs into a `str`.
+
+use super::*;
+
+/// A double-ended queue of UTF-8 text that derefs into a `str`.
+///
+/// A `StrDeque` stores its contents in a `SliceDeque<u8>` and only allows
+/// adding and removing whole characters, so that its contents are always
+/// valid UTF-8 and can be read as a `&str` without validating them again.
+/// This makes it suitable for scrollback and log-tail buffers, which are
+/// appended to at the back and trimmed at the front.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::StrDeque;
+/// use std::fmt::Write;
+///
+/// let mut log = StrDeque::new();
+/// for i in 0..5 {
+///     writeln!(log, "line {}", i).unwrap();
+/// }
+/// log.truncate_front_lines(2);
+/// assert_eq!(log, "line 3\nline 4\n");
+/// assert_eq!(log.pop_back_char(), Some('\n'));
+/// assert_eq!(log.lines().last(), Some("line 4"));
+/// ```
+#[derive(Clone, Default)]
+pub struct StrDeque {
+    /// UTF-8 encoded contents.
+    bytes: SliceDeque<u8>,
+}
+
+impl StrDeque {
+    /// Creates a new empty `StrDeque`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            bytes: SliceDeque::new(),
+        }
+    }
+
+    /// Creates a new empty `StrDeque` with capacity for at least `n` bytes.
+    ///
+    /// # Panics
+    ///
+    /// On OOM.
+    #[inline]
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            bytes: SliceDeque::with_capacity(n),
+        }
+    }
+
+    /// Converts a deque of bytes into a `StrDeque`.
+    ///
+    /// # Errors
+    ///
+    /// If `bytes` is not valid UTF-8. The bytes can be recovered from the
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate slice_deque;
+    /// # use slice_deque::StrDeque;
+    /// # fn main() {
+    /// let s = StrDeque::from_utf8(sdeq![b'h', b'i']).unwrap();
+    /// assert_eq!(s, "hi");
+    /// let e = StrDeque::from_utf8(sdeq![b'h', 0xff]).unwrap_err();
+    /// assert_eq!(e.utf8_error().valid_up_to(), 1);
+    /// assert_eq!(e.into_bytes(), [b'h', 0xff]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn from_utf8(bytes: SliceDeque<u8>) -> Result<Self, FromUtf8Error> {
+        match str::from_utf8(&bytes) {
+            Ok(_) => Ok(Self { bytes }),
+            Err(error) => Err(FromUtf8Error { bytes, error }),
+        }
+    }
+
+    /// Converts a deque of bytes into a `StrDeque` without checking that it
+    /// is valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must be valid UTF-8.
+    #[inline]
+    pub unsafe fn from_utf8_unchecked(bytes: SliceDeque<u8>) -> Self {
+        debug_assert!(str::from_utf8(&bytes).is_ok());
+        Self { bytes }
+    }
+
+    /// Converts the `StrDeque` into its bytes.
+    #[inline]
+    pub fn into_bytes(self) -> SliceDeque<u8> {
+        self.bytes
+    }
+
+    /// Returns the contents as a string slice.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.bytes) }
+    }
+
+    /// Returns the contents as a mutable string slice.
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        unsafe { str::from_utf8_unchecked_mut(&mut self.bytes) }
+    }
+
+    /// Length of the contents in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Is the deque empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Number of bytes that the deque can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    /// Reserves capacity for inserting at least `additional` bytes without
+    /// reallocating.
+    ///
+    /// # Panics
+    ///
+    /// If the new capacity overflows `usize` or on OOM.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.bytes.reserve(additional);
+    }
+
+    /// Appends `s` to the back of the deque.
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+
+    /// Prepends `s` to the front of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::StrDeque;
+    /// let mut s = StrDeque::from("world");
+    /// s.push_front_str("hello ");
+    /// assert_eq!(s, "hello world");
+    /// ```
+    #[inline]
+    pub fn push_front_str(&mut self, s: &str) {
+        self.bytes.extend_front_from_slice(s.as_bytes());
+    }
+
+    /// Appends `c` to the back of the deque.
+    #[inline]
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Prepends `c` to the front of the deque.
+    #[inline]
+    pub fn push_front(&mut self, c: char) {
+        self.push_front_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Removes the first character and returns it, or `None` if the deque
+    /// is empty.
+    #[inline]
+    pub fn pop_front_char(&mut self) -> Option<char> {
+        let c = self.chars().next()?;
+        let len = self.len() - c.len_utf8();
+        self.bytes.truncate_front(len);
+        Some(c)
+    }
+
+    /// Removes the last character and returns it, or `None` if the deque is
+    /// empty.
+    #[inline]
+    pub fn pop_back_char(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        let len = self.len() - c.len_utf8();
+        self.bytes.truncate_back(len);
+        Some(c)
+    }
+
+    /// Removes the first `n` characters, or all of them if there are fewer
+    /// than `n`, and returns them in an iterator.
+    ///
+    /// Note: the characters are removed even if the iterator is not
+    /// consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::StrDeque;
+    /// let mut s = StrDeque::from("αβγδ");
+    /// assert_eq!(s.drain_front_chars(2).as_str(), "αβ");
+    /// assert_eq!(s, "γδ");
+    /// ```
+    #[inline]
+    pub fn drain_front_chars(&mut self, n: usize) -> CharDrain<'_> {
+        let end = self
+            .char_indices()
+            .nth(n)
+            .map_or(self.len(), |(idx, _)| idx);
+        CharDrain {
+            drain: self.bytes.drain(..end),
+            hidden: 0,
+        }
+    }
+
+    /// Returns the first complete line, that is, the text before the first
+    /// `'\n'`, or `None` if there is no complete line.
+    ///
+    /// Like `str::lines`, a `'\r'` before the `'\n'` is not part of the
+    /// line.
+    #[inline]
+    pub fn front_line(&self) -> Option<&str> {
+        let end = self.find('\n')?;
+        Some(strip_cr(&self[..end]))
+    }
+
+    /// Removes the first complete line, including its line ending, and
+    /// returns the line without the line ending in an iterator.
+    ///
+    /// Returns `None` if there is no complete line, so that an incomplete
+    /// last line stays in the deque until it is terminated.
+    ///
+    /// Note: the line is removed even if the iterator is not consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::StrDeque;
+    /// let mut s = StrDeque::from("GET / HTTP/1.1\r\nHost: exa");
+    /// assert_eq!(
+    ///     s.pop_front_line().map(|l| l.as_str() == "GET / HTTP/1.1"),
+    ///     Some(true)
+    /// );
+    /// assert!(s.pop_front_line().is_none());
+    /// assert_eq!(s, "Host: exa");
+    /// ```
+    #[inline]
+    pub fn pop_front_line(&mut self) -> Option<CharDrain<'_>> {
+        let end = self.find('\n')?;
+        let hidden = end + 1 - strip_cr(&self[..end]).len();
+        Some(CharDrain {
+            drain: self.bytes.drain(..=end),
+            hidden,
+        })
+    }
+
+    /// Removes lines from the front until at most the last `n` lines remain.
+    ///
+    /// Lines are counted like `str::lines` does: the last line does not need
+    /// to be terminated by a `'\n'`.
+    #[inline]
+    pub fn truncate_front_lines(&mut self, n: usize) {
+        if n == 0 {
+            self.clear();
+            return;
+        }
+        let bytes = self.as_bytes();
+        let mut search = match bytes.last() {
+            Some(&b'\n') => &bytes[..bytes.len() - 1],
+            _ => bytes,
+        };
+        for _ in 0..n {
+            match search.iter().rposition(|&b| b == b'\n') {
+                Some(idx) => search = &search[..idx],
+                None => return,
+            }
+        }
+        let len = self.len() - search.len() - 1;
+        self.bytes.truncate_front(len);
+    }
+
+    /// Shortens the deque to the last `n` characters.
+    ///
+    /// If the deque has `n` or fewer characters, this has no effect.
+    #[inline]
+    pub fn truncate_front_chars(&mut self, n: usize) {
+        if n == 0 {
+            self.clear();
+            return;
+        }
+        if let Some((idx, _)) = self.char_indices().rev().nth(n - 1) {
+            let len = self.len() - idx;
+            self.bytes.truncate_front(len);
+        }
+    }
+
+    /// Removes all text from the deque.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+}
+
+/// Removes a trailing `'\r'` from `line`.
+#[inline]
+fn strip_cr(line: &str) -> &str {
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+impl ops::Deref for StrDeque {
+    type Target = str;
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl ops::DerefMut for StrDeque {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl convert::AsRef<str> for StrDeque {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl convert::AsRef<[u8]> for StrDeque {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<'a> From<&'a str> for StrDeque {
+    #[inline]
+    fn from(s: &'a str) -> Self {
+        let mut deq = Self::with_capacity(s.len());
+        deq.push_str(s);
+        deq
+    }
+}
+
+impl fmt::Write for StrDeque {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl fmt::Display for StrDeque {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Debug for StrDeque {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl hash::Hash for StrDeque {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialEq for StrDeque {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StrDeque {}
+
+impl PartialEq<str> for StrDeque {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for StrDeque {
+    #[inline]
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl Extend<char> for StrDeque {
+    #[inline]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for c in iter {
+            self.push(c);
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for StrDeque {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
+
+impl iter::FromIterator<char> for StrDeque {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut deq = Self::new();
+        deq.extend(iter);
+        deq
+    }
+}
+
+/// A draining iterator over characters removed from a `StrDeque`.
+///
+/// This `struct` is created by [`StrDeque::drain_front_chars`] and
+/// [`StrDeque::pop_front_line`].
+///
+/// [`StrDeque::drain_front_chars`]: struct.StrDeque.html#method.drain_front_chars
+/// [`StrDeque::pop_front_line`]: struct.StrDeque.html#method.pop_front_line
+pub struct CharDrain<'a> {
+    /// The bytes being removed.
+    drain: Drain<'a, u8>,
+    /// Number of bytes at the end of the drain that are not yielded, e.g.,
+    /// a line ending.
+    hidden: usize,
+}
+
+impl<'a> CharDrain<'a> {
+    /// Returns the remaining characters of this iterator as a string slice.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        let bytes = self.drain.as_slice();
+        unsafe {
+            str::from_utf8_unchecked(&bytes[..bytes.len() - self.hidden])
+        }
+    }
+}
+
+impl<'a> Iterator for CharDrain<'a> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next()?;
+        for _ in 0..c.len_utf8() {
+            self.drain.next();
+        }
+        Some(c)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.drain.len() - self.hidden;
+        (len.div_ceil(4), Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for CharDrain<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        let end = self.drain.len() - self.hidden;
+        let mut skipped = 0;
+        // Skip the hidden bytes and the character, and re-hide the former:
+        while skipped < self.hidden + c.len_utf8() {
+            self.drain.next_back();
+            skipped += 1;
+        }
+        debug_assert_eq!(self.drain.len(), end - c.len_utf8());
+        self.hidden = 0;
+        Some(c)
+    }
+}
+
+impl<'a> iter::FusedIterator for CharDrain<'a> {}
+
+impl<'a> fmt::Debug for CharDrain<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CharDrain").field(&self.as_str()).finish()
+    }
+}
+
+/// A possible error value when converting a `SliceDeque<u8>` into a
+/// `StrDeque`.
+///
+/// This `struct` is created by [`StrDeque::from_utf8`].
+///
+/// [`StrDeque::from_utf8`]: struct.StrDeque.html#method.from_utf8
+pub struct FromUtf8Error {
+    /// The bytes that were being converted.
+    bytes: SliceDeque<u8>,
+    /// Why the conversion failed.
+    error: str::Utf8Error,
+}
+
+impl FromUtf8Error {
+    /// Returns the bytes that were being converted.
+    #[inline]
+    pub fn into_bytes(self) -> SliceDeque<u8> {
+        self.bytes
+    }
+
+    /// Returns details about the conversion failure.
+    #[inline]
+    pub fn utf8_error(&self) -> str::Utf8Error {
+        self.error
+    }
+}
+
+impl fmt::Debug for FromUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FromUtf8Error")
+            .field("bytes", &self.bytes)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl fmt::Display for FromUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl ::std::error::Error for FromUtf8Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn chars_at_both_ends() {
+        let mut s = StrDeque::new();
+        s.push('β');
+        s.push_str("cd");
+        s.push_front('a');
+        s.push_front_str("¡");
+        assert_eq!(s, "¡aβcd");
+        assert_eq!(s.pop_front_char(), Some('¡'));
+        assert_eq!(s.pop_back_char(), Some('d'));
+        assert_eq!(s.pop_back_char(), Some('c'));
+        assert_eq!(s.pop_back_char(), Some('β'));
+        assert_eq!(s.pop_front_char(), Some('a'));
+        assert_eq!(s.pop_front_char(), None);
+        assert_eq!(s.pop_back_char(), None);
+
+        s.extend("→x→".chars());
+        s.truncate_front_chars(2);
+        assert_eq!(s, "x→");
+        s.truncate_front_chars(5);
+        assert_eq!(s, "x→");
+        s.make_ascii_uppercase();
+        assert_eq!(s.as_str(), "X→");
+    }
+
+    #[test]
+    fn drain_chars() {
+        let mut s: StrDeque = "añb€c".chars().collect();
+        {
+            let mut d = s.drain_front_chars(4);
+            assert_eq!(d.next(), Some('a'));
+            assert_eq!(d.next_back(), Some('€'));
+            assert_eq!(d.as_str(), "ñb");
+            assert_eq!(d.collect::<Vec<_>>(), ['ñ', 'b']);
+        }
+        assert_eq!(s, "c");
+        assert_eq!(s.drain_front_chars(10).count(), 1);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn lines() {
+        let mut s = StrDeque::new();
+        write!(s, "one\r\ntwo\n\nfour").unwrap();
+        assert_eq!(s.front_line(), Some("one"));
+        {
+            let mut l = s.pop_front_line().unwrap();
+            assert_eq!(l.next_back(), Some('e'));
+            assert_eq!(l.collect::<Vec<_>>(), ['o', 'n']);
+        }
+        assert_eq!(s.pop_front_line().unwrap().as_str(), "two");
+        assert_eq!(s.pop_front_line().unwrap().as_str(), "");
+        assert_eq!(s.front_line(), None);
+        assert!(s.pop_front_line().is_none());
+        assert_eq!(s, "four");
+
+        let mut s = StrDeque::from("a\nb\nc");
+        s.truncate_front_lines(3);
+        assert_eq!(s, "a\nb\nc");
+        s.truncate_front_lines(2);
+        assert_eq!(s, "b\nc");
+        s.push('\n');
+        s.truncate_front_lines(1);
+        assert_eq!(s, "c\n");
+        s.truncate_front_lines(0);
+        assert!(s.is_empty());
+    }
+}
@@ -0,0 +1,427 @@
+//! Sliding windows over a stream of samples with running aggregates.
+
+use super::*;
+
+/// A numeric sample that can be aggregated by a [`SlidingWindow`].
+///
+/// Sums are accumulated in a wider type, `Sum`, so that, e.g., a window of
+/// `u8` samples does not overflow.
+///
+/// [`SlidingWindow`]: struct.SlidingWindow.html
+pub trait Sample: Copy + PartialOrd {
+    /// Type in which samples are summed.
+    type Sum: Copy + fmt::Debug;
+    /// The sum of no samples.
+    fn zero() -> Self::Sum;
+    /// Adds the sample to `sum`.
+    fn add_to(self, sum: Self::Sum) -> Self::Sum;
+    /// Subtracts the sample from `sum`.
+    fn sub_from(self, sum: Self::Sum) -> Self::Sum;
+    /// Converts `sum` into an `f64` to compute means.
+    fn sum_to_f64(sum: Self::Sum) -> f64;
+}
+
+macro_rules! impl_sample {
+    ($($t:ty => $sum:ty, $zero:expr;)*) => {
+        $(
+            impl Sample for $t {
+                type Sum = $sum;
+                #[inline]
+                fn zero() -> $sum {
+                    $zero
+                }
+                #[inline]
+                fn add_to(self, sum: $sum) -> $sum {
+                    sum + self as $sum
+                }
+                #[inline]
+                fn sub_from(self, sum: $sum) -> $sum {
+                    sum - self as $sum
+                }
+                #[inline]
+                fn sum_to_f64(sum: $sum) -> f64 {
+                    sum as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_sample! {
+    u8 => u64, 0;
+    u16 => u64, 0;
+    u32 => u64, 0;
+    u64 => u128, 0;
+    usize => u128, 0;
+    i8 => i64, 0;
+    i16 => i64, 0;
+    i32 => i64, 0;
+    i64 => i128, 0;
+    isize => i128, 0;
+    f32 => f64, 0.;
+    f64 => f64, 0.;
+}
+
+/// What bounds a sliding window.
+#[derive(Copy, Clone, Debug)]
+enum Bound {
+    /// At most this many samples.
+    Len(usize),
+    /// Samples pushed at time `t` expire at time `t + duration`.
+    Duration(u64),
+}
+
+/// A sliding window over a stream of samples.
+///
+/// The window keeps either the last `n` samples pushed, or the samples pushed
+/// during the last `duration` time units. Its contents are always available
+/// as a contiguous slice, and the minimum, maximum, sum and mean of the
+/// samples are maintained in `O(1)` amortized time per sample.
+///
+/// The minimum and maximum are tracked with monotonic queues of candidates,
+/// which are not well defined for samples that do not have a total order,
+/// like floating-point `NaN`s. Floating-point sums are updated by adding
+/// and subtracting samples, which can accumulate rounding errors over long
+/// streams.
+///
+/// # Examples
+///
+/// ```rust
+/// # use slice_deque::SlidingWindow;
+/// let mut w = SlidingWindow::with_len(3);
+/// for &x in &[5_u32, 1, 4, 2, 8] {
+///     w.push(x);
+/// }
+/// assert_eq!(w.as_slice(), &[4, 2, 8]);
+/// assert_eq!(w.min(), Some(2));
+/// assert_eq!(w.max(), Some(8));
+/// assert_eq!(w.sum(), 14);
+/// assert_eq!(w.mean(), Some(14. / 3.));
+/// ```
+pub struct SlidingWindow<T: Sample> {
+    /// Samples in the window.
+    values: SliceDeque<T>,
+    /// Times at which the samples were pushed, for time-bounded windows.
+    times: SliceDeque<u64>,
+    /// Sequence numbers of the candidates for the minimum, in increasing
+    /// order of sequence number and value.
+    min: SliceDeque<u64>,
+    /// Sequence numbers of the candidates for the maximum, in increasing
+    /// order of sequence number and decreasing order of value.
+    max: SliceDeque<u64>,
+    /// Sequence number of the first sample in the window.
+    front_seq: u64,
+    /// Sum of the samples in the window.
+    sum: T::Sum,
+    /// What bounds the window.
+    bound: Bound,
+}
+
+impl<T: Sample> SlidingWindow<T> {
+    /// Creates an empty window holding the last `n` samples pushed.
+    #[inline]
+    pub fn with_len(n: usize) -> Self {
+        Self::new(Bound::Len(n))
+    }
+
+    /// Creates an empty window holding the samples pushed during the last
+    /// `duration` time units.
+    ///
+    /// The unit of time is up to the caller: samples are pushed with
+    /// `push_at`.
+    #[inline]
+    pub fn with_duration(duration: u64) -> Self {
+        Self::new(Bound::Duration(duration))
+    }
+
+    /// Creates an empty window.
+    #[inline]
+    fn new(bound: Bound) -> Self {
+        Self {
+            values: SliceDeque::new(),
+            times: SliceDeque::new(),
+            min: SliceDeque::new(),
+            max: SliceDeque::new(),
+            front_seq: 0,
+            sum: T::zero(),
+            bound,
+        }
+    }
+
+    /// Number of samples in the window.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Is the window empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Samples in the window, from the oldest to the newest.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.values.as_slice()
+    }
+
+    /// Smallest sample in the window, or `None` if it is empty.
+    #[inline]
+    pub fn min(&self) -> Option<T> {
+        self.min.front().map(|&seq| self.value(seq))
+    }
+
+    /// Largest sample in the window, or `None` if it is empty.
+    #[inline]
+    pub fn max(&self) -> Option<T> {
+        self.max.front().map(|&seq| self.value(seq))
+    }
+
+    /// Sum of the samples in the window.
+    #[inline]
+    pub fn sum(&self) -> T::Sum {
+        self.sum
+    }
+
+    /// Mean of the samples in the window, or `None` if it is empty.
+    #[inline]
+    pub fn mean(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(T::sum_to_f64(self.sum) / self.len() as f64)
+    }
+
+    /// Pushes `value` into a length-bounded window, removing the oldest
+    /// sample if the window is full.
+    ///
+    /// Returns the removed sample, if any. If the length of the window is
+    /// zero, `value` itself is returned.
+    ///
+    /// # Panics
+    ///
+    /// If the window is time-bounded.
+    #[inline]
+    pub fn push(&mut self, value: T) -> Option<T> {
+        let n = match self.bound {
+            Bound::Len(n) => n,
+            Bound::Duration(_) => {
+                panic!("time-bounded windows require `push_at`")
+            }
+        };
+        if n == 0 {
+            return Some(value);
+        }
+        let evicted = if self.len() == n {
+            self.pop_front()
+        } else {
+            None
+        };
+        self.push_back(value);
+        evicted
+    }
+
+    /// Pushes `value` at time `now` into a time-bounded window, removing the
+    /// samples that expired.
+    ///
+    /// # Panics
+    ///
+    /// If the window is length-bounded, or if `now` is earlier than the time
+    /// of the previous sample.
+    #[inline]
+    pub fn push_at(&mut self, now: u64, value: T) {
+        self.expire(now);
+        self.times.push_back(now);
+        self.push_back(value);
+    }
+
+    /// Removes the samples of a time-bounded window that expired at time
+    /// `now`.
+    ///
+    /// # Panics
+    ///
+    /// If the window is length-bounded, or if `now` is earlier than the time
+    /// of the newest sample.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use slice_deque::SlidingWindow;
+    /// let mut w = SlidingWindow::with_duration(10);
+    /// w.push_at(0, 1.5);
+    /// w.push_at(5, 2.5);
+    /// w.expire(10);
+    /// assert_eq!(w.as_slice(), &[2.5]);
+    /// w.expire(15);
+    /// assert_eq!(w.mean(), None);
+    /// ```
+    #[inline]
+    pub fn expire(&mut self, now: u64) {
+        let duration = match self.bound {
+            Bound::Duration(d) => d,
+            Bound::Len(_) => {
+                panic!("length-bounded windows do not expire samples")
+            }
+        };
+        if let Some(&last) = self.times.back() {
+            assert!(
+                now >= last,
+                "time went backwards: {} is earlier than {}",
+                now,
+                last
+            );
+        }
+        while let Some(&t) = self.times.front() {
+            if now - t < duration {
+                break;
+            }
+            self.times.pop_front();
+            self.pop_front();
+        }
+    }
+
+    /// Removes all samples from the window.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.front_seq += self.len() as u64;
+        self.values.clear();
+        self.times.clear();
+        self.min.clear();
+        self.max.clear();
+        self.sum = T::zero();
+    }
+
+    /// Sample with sequence number `seq`.
+    #[inline]
+    fn value(&self, seq: u64) -> T {
+        self.values[(seq - self.front_seq) as usize]
+    }
+
+    /// Appends `value` to the window, updating the aggregates.
+    #[inline]
+    fn push_back(&mut self, value: T) {
+        let seq = self.front_seq + self.len() as u64;
+        while let Some(&s) = self.min.back() {
+            if self.value(s) <= value {
+                break;
+            }
+            self.min.pop_back();
+        }
+        while let Some(&s) = self.max.back() {
+            if self.value(s) >= value {
+                break;
+            }
+            self.max.pop_back();
+        }
+        self.min.push_back(seq);
+        self.max.push_back(seq);
+        self.values.push_back(value);
+        self.sum = value.add_to(self.sum);
+    }
+
+    /// Removes the oldest sample from the window, updating the aggregates.
+    #[inline]
+    fn pop_front(&mut self) -> Option<T> {
+        let value = self.values.pop_front()?;
+        let seq = self.front_seq;
+        self.front_seq += 1;
+        if self.min.front() == Some(&seq) {
+            self.min.pop_front();
+        }
+        if self.max.front() == Some(&seq) {
+            self.max.pop_front();
+        }
+        self.sum = value.sub_from(self.sum);
+        Some(value)
+    }
+}
+
+impl<T: Sample> ops::Deref for SlidingWindow<T> {
+    type Target = [T];
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T: Sample + fmt::Debug> fmt::Debug for SlidingWindow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", self.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the aggregates of `w` against the naive computation.
+    fn check<T: Sample + fmt::Debug>(w: &SlidingWindow<T>)
+    where
+        T::Sum: PartialEq,
+    {
+        let s = w.as_slice();
+        let naive_min = s.iter().cloned().fold(None, |m: Option<T>, x| {
+            Some(m.map_or(x, |m| if x < m { x } else { m }))
+        });
+        let naive_max = s.iter().cloned().fold(None, |m: Option<T>, x| {
+            Some(m.map_or(x, |m| if x > m { x } else { m }))
+        });
+        let naive_sum = s.iter().fold(T::zero(), |acc, &x| x.add_to(acc));
+        assert_eq!(w.min(), naive_min);
+        assert_eq!(w.max(), naive_max);
+        assert_eq!(w.sum(), naive_sum);
+    }
+
+    #[test]
+    fn len_bounded() {
+        let mut w = SlidingWindow::with_len(5);
+        let mut x = 17_u8;
+        for i in 0..1000 {
+            x = x.wrapping_mul(31).wrapping_add(7);
+            let evicted = w.push(x);
+            assert_eq!(evicted.is_some(), i >= 5);
+            check(&w);
+        }
+        assert_eq!(w.len(), 5);
+        w.clear();
+        check(&w);
+        assert_eq!(w.mean(), None);
+        w.push(255);
+        w.push(255);
+        assert_eq!(w.sum(), 510);
+
+        let mut w = SlidingWindow::with_len(0);
+        assert_eq!(w.push(1_i32), Some(1));
+        assert!(w.is_empty());
+    }
+
+    #[test]
+    fn time_bounded() {
+        let mut w = SlidingWindow::with_duration(10);
+        for t in 0..100_u64 {
+            w.push_at(t * 3, (t % 7) as i64 - 3);
+            assert!(w.len() <= 4);
+            check(&w);
+        }
+        assert_eq!(w.len(), 4);
+        w.expire(1000);
+        assert!(w.is_empty());
+        check(&w);
+    }
+
+    #[test]
+    #[should_panic]
+    fn time_goes_backwards() {
+        let mut w = SlidingWindow::with_duration(10);
+        w.push_at(5, 1.0_f32);
+        w.push_at(4, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_into_time_bounded() {
+        let mut w = SlidingWindow::with_duration(10);
+        w.push(1_u32);
+    }
+}